@@ -0,0 +1,32 @@
+// Aggregates summarize the values stored beneath each node, e.g. a sum of counts or a max score,
+// so that the combined value of all keys under a prefix is available without visiting them.
+// The summary type itself implements the trait, forming a monoid over the trie's values:
+// combine must be associative and empty its identity, combine is applied in key order
+
+pub trait TrieAggregate<V> {
+    // Whether summaries carry no information, in which case they are never computed
+    const TRIVIAL: bool = false;
+
+    // Identity summary e.g. for a prefix with no keys
+    fn empty() -> Self;
+
+    // Summary of a single value
+    fn lift(value: &V) -> Self;
+
+    // Combines two summaries, self covering keys that precede other's keys
+    fn combine(&self, other: &Self) -> Self;
+}
+
+// No aggregation, the default for tries
+impl<V> TrieAggregate<V> for () {
+    const TRIVIAL: bool = true;
+
+    #[inline]
+    fn empty() -> Self {}
+
+    #[inline]
+    fn lift(_value: &V) -> Self {}
+
+    #[inline]
+    fn combine(&self, _other: &Self) -> Self {}
+}
@@ -0,0 +1,362 @@
+use std::mem;
+
+// Bit level radix trie, unlike Trie whose labels are byte granular, keys here are prefixes of
+// arbitrary bit length e.g. CIDR blocks. Each node stores its full key prefix along with the
+// prefix's bit length, a node's children are indexed by the first bit following its prefix,
+// hence paths are compressed as only nodes holding values or forking paths are kept
+
+#[derive(Clone, Debug)]
+pub struct BitTrie<V> {
+    root: BitNode<V>,
+    size: usize,
+}
+
+#[derive(Clone, Debug)]
+struct BitNode<V> {
+    key: Vec<u8>, // prefix bits, with any bits past len zeroed
+    len: usize,   // prefix length in bits
+    value: Option<V>,
+    children: [Option<Box<BitNode<V>>>; 2],
+}
+
+// Returns bit i of bytes, counting from the most significant bit
+#[inline]
+fn bit(bytes: &[u8], i: usize) -> usize {
+    bytes.get(i / 8).map_or(0, |b| ((b >> (7 - i % 8)) & 1) as usize)
+}
+
+// Returns the number of leading bits a and b have in common, up to max bits
+fn common_bits(a: &[u8], b: &[u8], max: usize) -> usize {
+    let mut count = 0;
+
+    for (x, y) in a.iter().zip(b.iter()) {
+        let diff = x ^ y;
+
+        if diff != 0 {
+            count += diff.leading_zeros() as usize;
+            break
+        }
+
+        count += 8;
+    }
+
+    count.min(max)
+}
+
+// Copies the first len bits of bytes, zeroing the remaining bits of the last byte.
+// Every operation taking a key and a prefix length goes through here, so all of them
+// panic alike should the length exceed the key's bits
+fn truncate(bytes: &[u8], len: usize) -> Vec<u8> {
+    assert!(len <= bytes.len() * 8, "prefix length exceeds key bits");
+
+    let mut key = bytes[..len.div_ceil(8)].to_vec();
+
+    if !len.is_multiple_of(8) {
+        let last = key.len() - 1;
+        key[last] &= 0xFF << (8 - len % 8);
+    }
+
+    key
+}
+
+impl<V> BitNode<V> {
+    fn new(key: Vec<u8>, len: usize, value: Option<V>) -> Self {
+        BitNode {
+            key,
+            len,
+            value,
+            children: [None, None],
+        }
+    }
+
+    // Whether node's prefix is a prefix of the given bits
+    #[inline]
+    fn covers(&self, key: &[u8], len: usize) -> bool {
+        self.len <= len && common_bits(&self.key, key, self.len) == self.len
+    }
+
+    #[inline]
+    fn child(&self, key: &[u8], len: usize) -> Option<&BitNode<V>> {
+        if self.len >= len {
+            return None
+        }
+
+        self.children[bit(key, self.len)].as_deref()
+    }
+}
+
+impl<V> BitTrie<V> {
+    pub fn new() -> Self {
+        BitTrie {
+            root: BitNode::new(vec![], 0, None),
+            size: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    // Inserts value for the first len bits of key, returning the previous value if any.
+    // Bits of key past len are ignored
+    pub fn insert(&mut self, key: &[u8], len: usize, value: V) -> Option<V> {
+        let key = truncate(key, len);
+        let mut current = &mut self.root;
+
+        let result = loop {
+            if current.len == len {
+                break current.value.replace(value)
+            }
+
+            let slot = &mut current.children[bit(&key, current.len)];
+
+            let Some(child) = slot else {
+                *slot = Some(Box::new(BitNode::new(key, len, Some(value))));
+                break None
+            };
+
+            let common = common_bits(&child.key, &key, child.len.min(len));
+
+            // Child's prefix lies on key's path, continue below
+            if common == child.len {
+                current = &mut **slot.as_mut().unwrap();
+                continue
+            }
+
+            let old = slot.take().unwrap();
+
+            // Key is a prefix of child's prefix, so key's node sits above the child,
+            // otherwise the two diverge and a fork node holding their common bits is needed
+            let index = bit(&old.key, common);
+
+            let node = if common == len {
+                let mut node = BitNode::new(key, len, Some(value));
+                node.children[index] = Some(old);
+                node
+            } else {
+                let mut fork = BitNode::new(truncate(&key, common), common, None);
+                fork.children[1 - index] = Some(Box::new(BitNode::new(key, len, Some(value))));
+                fork.children[index] = Some(old);
+                fork
+            };
+
+            *slot = Some(Box::new(node));
+            break None
+        };
+
+        if result.is_none() {
+            self.size += 1;
+        }
+
+        result
+    }
+
+    // Retrieves value stored for exactly the first len bits of key
+    pub fn get(&self, key: &[u8], len: usize) -> Option<&V> {
+        let key = truncate(key, len);
+        let mut current = &self.root;
+
+        while current.len < len {
+            current = current.child(&key, len).filter(|c| c.covers(&key, len))?;
+        }
+
+        current.value.as_ref().filter(|_| current.len == len)
+    }
+
+    // Removes value stored for exactly the first len bits of key, nodes left without a value
+    // are pruned or spliced out so that paths stay compressed
+    pub fn remove(&mut self, key: &[u8], len: usize) -> Option<V> {
+        let key = truncate(key, len);
+
+        let result = if len == 0 {
+            self.root.value.take()
+        } else {
+            let slot = &mut self.root.children[bit(&key, 0)];
+            Self::remove_helper(slot, &key, len)
+        };
+
+        if result.is_some() {
+            self.size -= 1;
+        }
+
+        result
+    }
+
+    fn remove_helper(slot: &mut Option<Box<BitNode<V>>>, key: &[u8], len: usize) -> Option<V> {
+        let node = slot.as_mut().filter(|n| n.covers(key, len))?;
+
+        let result = if node.len == len {
+            node.value.take()
+        } else {
+            let index = bit(key, node.len);
+            Self::remove_helper(&mut node.children[index], key, len)
+        };
+
+        // Restore compressed form, a node without a value must fork
+        if node.value.is_none() {
+            match &mut node.children {
+                [None, None] => *slot = None,
+                [Some(_), None] | [None, Some(_)] => {
+                    let [a, b] = mem::take(&mut node.children);
+                    *slot = a.or(b);
+                },
+                _ => (),
+            }
+        }
+
+        result
+    }
+
+    // Finds the longest stored prefix of the first len bits of key, returning the
+    // prefix's bit length along with its value
+    pub fn longest_match(&self, key: &[u8], len: usize) -> Option<(usize, &V)> {
+        self.matches(key, len).last()
+    }
+
+    // Iterates all stored prefixes of the first len bits of key, shortest first,
+    // yielding each prefix's bit length along with its value
+    pub fn matches(&self, key: &[u8], len: usize) -> MatchesIter<'_, V> {
+        MatchesIter {
+            current: Some(&self.root),
+            key: truncate(key, len),
+            len,
+        }
+    }
+
+    // Iterates all stored prefixes which start with the first len bits of key (including
+    // the key itself) in bit order, yielding each prefix's bits and bit length along with its value
+    pub fn covered_by(&self, key: &[u8], len: usize) -> CoveredIter<'_, V> {
+        let key = truncate(key, len);
+        let mut current = &self.root;
+
+        // Descend until the first node at or below the key's bit length
+        while current.len < len {
+            match current.child(&key, len) {
+                Some(child) if common_bits(&child.key, &key, len.min(child.len)) == len.min(child.len) => current = child,
+                _ => return CoveredIter { stack: vec![] },
+            }
+        }
+
+        CoveredIter { stack: vec![current] }
+    }
+
+    // Iterates all stored prefixes in bit order
+    pub fn iter(&self) -> CoveredIter<'_, V> {
+        CoveredIter { stack: vec![&self.root] }
+    }
+}
+
+impl<V> Default for BitTrie<V> {
+    fn default() -> Self {
+        BitTrie::new()
+    }
+}
+
+// Iterates the nodes along a key's path whose prefixes cover the key
+#[derive(Clone, Debug)]
+pub struct MatchesIter<'a, V> {
+    current: Option<&'a BitNode<V>>,
+    key: Vec<u8>,
+    len: usize,
+}
+
+impl<'a, V> Iterator for MatchesIter<'a, V> {
+    type Item = (usize, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.current {
+            self.current = node.child(&self.key, self.len).filter(|c| c.covers(&self.key, self.len));
+
+            if let Some(v) = node.value.as_ref() {
+                return Some((node.len, v))
+            }
+        }
+
+        None
+    }
+}
+
+// Preorder iteration over a subtree, a prefix precedes the prefixes extending it
+#[derive(Clone, Debug)]
+pub struct CoveredIter<'a, V> {
+    stack: Vec<&'a BitNode<V>>,
+}
+
+impl<'a, V> Iterator for CoveredIter<'a, V> {
+    type Item = (&'a [u8], usize, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            self.stack.extend(node.children.iter().rev().flatten().map(|b| &**b));
+
+            if let Some(v) = node.value.as_ref() {
+                return Some((&node.key, node.len, v))
+            }
+        }
+
+        None
+    }
+}
+
+// BitTrie unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_bit_insert_and_match() {
+        let mut trie = BitTrie::new();
+
+        assert_eq!(None, trie.insert(&[10], 8, "a"));
+        assert_eq!(None, trie.insert(&[10, 1], 16, "b"));
+        assert_eq!(None, trie.insert(&[10, 0x80], 9, "c"));
+        assert_eq!(None, trie.insert(&[11], 8, "d"));
+        assert_eq!(Some("b"), trie.insert(&[10, 1, 7], 16, "e"));
+        assert_eq!(4, trie.len());
+
+        assert_eq!(Some((16, &"e")), trie.longest_match(&[10, 1, 2, 3], 32));
+        assert_eq!(Some((9, &"c")), trie.longest_match(&[10, 0xC0, 0, 0], 32));
+        assert_eq!(Some((8, &"a")), trie.longest_match(&[10, 2, 0, 0], 32));
+        assert_eq!(None, trie.longest_match(&[12, 0, 0, 0], 32));
+        assert_eq!(Some(&"a"), trie.get(&[10, 0xFF], 8));
+        assert_eq!(None, trie.get(&[10], 7));
+
+        let bits = trie.iter().map(|(_, len, v)| (len, *v)).collect::<Vec<_>>();
+        assert_eq!(vec![(8, "a"), (16, "e"), (9, "c"), (8, "d")], bits);
+    }
+
+    #[test]
+    fn check_bit_remove() {
+        let mut trie = BitTrie::new();
+
+        trie.insert(&[10], 8, 1);
+        trie.insert(&[10, 1], 16, 2);
+        trie.insert(&[10, 0x80], 9, 3);
+        trie.insert(&[], 0, 0);
+
+        assert_eq!(Some(1), trie.remove(&[10], 8));
+        assert_eq!(None, trie.remove(&[10], 8));
+        assert_eq!(Some((0, &0)), trie.longest_match(&[10, 2], 16));
+        assert_eq!(Some((16, &2)), trie.longest_match(&[10, 1], 16));
+
+        assert_eq!(Some(2), trie.remove(&[10, 1], 16));
+        assert_eq!(Some(3), trie.remove(&[10, 0x80], 9));
+        assert_eq!(Some(0), trie.remove(&[], 0));
+        assert!(trie.is_empty());
+        assert!(trie.root.children.iter().all(|c| c.is_none()));
+    }
+
+    #[test]
+    #[should_panic(expected = "prefix length exceeds key bits")]
+    fn check_bit_len_past_key() {
+        let mut trie = BitTrie::new();
+
+        trie.insert(&[10], 8, 1);
+        trie.covered_by(&[10], 9);
+    }
+}
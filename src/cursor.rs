@@ -0,0 +1,734 @@
+
+use std::mem;
+
+use crate::node::Node;
+use crate::aggregate::TrieAggregate;
+use crate::key::{TrieKey, decode_stored};
+use crate::trie::Trie;
+use crate::traverse::{KeyMatch, SuffixType, traverse_match};
+use crate::node::algebra::common_len;
+
+// A cursor is positioned either on a key or on a "ghost" position which sits
+// between the last key and the first key. Stepping next from the ghost position
+// moves to the first key, stepping prev moves to the last key. Keys are handed out
+// decoded, the byte level steps are kept crate internal
+
+// Tracks the node path from root to the current key node along with the current key
+// so that the cursor is able to step to adjacent keys without restarting from root
+#[derive(Clone, Debug)]
+pub struct Cursor<'a, K, V, A = ()> {
+    root: Option<&'a Node<K, V, A>>,
+    path: Vec<&'a Node<K, V, A>>,
+    key: Vec<u8>,
+}
+
+// Mutable cursor, as mutable refs along a node path can't be held onto the cursor holds the
+// path by value instead. The root is taken from the trie and each node descended into is
+// detached from its parent's edges, then attached again on the way back up, so steps cost
+// the same as a shared cursor's. The root is restored when the cursor is dropped, or before
+// a structural edit after which the cursor descends back to its key
+#[derive(Debug)]
+pub struct CursorMut<'a, K, V, A = ()> {
+    trie: &'a mut Trie<K, V, A>,
+    root: Option<Node<K, V, A>>,
+    size: usize,
+    path: Vec<Box<Node<K, V, A>>>, // detached nodes below the root, down to the current key
+    key: Vec<u8>,
+    ghost: bool,
+    touched: Vec<Vec<u8>>,         // keys handed out mutably, recorded once the root is restored
+}
+
+impl<'a, K, V, A> Cursor<'a, K, V, A> {
+    pub(crate) fn new(root: Option<&'a Node<K, V, A>>) -> Self {
+        Cursor {
+            root,
+            path: vec![],
+            key: vec![],
+        }
+    }
+
+    // Returns current key, None if at the ghost position
+    pub fn key(&self) -> Option<K::Owned>
+    where K: TrieKey
+    {
+        self.key_bytes().map(decode_stored::<K>)
+    }
+
+    pub(crate) fn key_bytes(&self) -> Option<&[u8]> {
+        self.path.last().map(|_| self.key.as_slice())
+    }
+
+    // Returns current key's value, None if at the ghost position
+    pub fn value(&self) -> Option<&'a V> {
+        self.path.last().and_then(|n| n.value())
+    }
+
+    // Positions cursor at the first key greater than or equal to token
+    pub fn seek(&mut self, token: K) -> Option<(K::Owned, &'a V)>
+    where K: TrieKey
+    {
+        self.seek_bytes(&token.encode());
+        self.current()
+    }
+
+    // Positions cursor at the first key
+    pub fn seek_first(&mut self) -> Option<(K::Owned, &'a V)>
+    where K: TrieKey
+    {
+        self.reset();
+        self.next()
+    }
+
+    // Positions cursor at the last key
+    pub fn seek_last(&mut self) -> Option<(K::Owned, &'a V)>
+    where K: TrieKey
+    {
+        self.reset();
+        self.prev()
+    }
+
+    // Steps to the next key in lexicographic order
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(K::Owned, &'a V)>
+    where K: TrieKey
+    {
+        self.next_bytes();
+        self.current()
+    }
+
+    // Steps to the previous key in lexicographic order
+    pub fn prev(&mut self) -> Option<(K::Owned, &'a V)>
+    where K: TrieKey
+    {
+        self.prev_bytes();
+        self.current()
+    }
+
+    pub(crate) fn next_bytes(&mut self) -> Option<(&[u8], &'a V)> {
+        match self.path.last().copied() {
+            None => {
+                if let Some(root) = self.root {
+                    self.push(root);
+                    self.leftmost();
+                }
+            },
+            // Descendants follow their ancestor key
+            Some(node) if node.first_edge().is_some() => {
+                self.push(node.first_edge().unwrap());
+                self.leftmost();
+            },
+            // The root holds the empty key, with nothing after it when it has no edges
+            Some(_) if self.path.len() == 1 => self.reset(),
+            Some(_) => {
+                let edge_key = self.pop();
+                self.advance(edge_key);
+            },
+        }
+
+        self.current_bytes()
+    }
+
+    pub(crate) fn prev_bytes(&mut self) -> Option<(&[u8], &'a V)> {
+        match self.path.last() {
+            None => {
+                if let Some(root) = self.root {
+                    self.push(root);
+                    self.rightmost();
+                }
+            },
+            // Nothing precedes the root's empty key
+            Some(_) if self.path.len() == 1 => self.reset(),
+            Some(_) => {
+                let edge_key = self.pop();
+                self.retreat(edge_key);
+            },
+        }
+
+        self.current_bytes()
+    }
+
+    // Walks token's path using traverse_match to find where token diverges from the
+    // trie's labels, then positions cursor at the first key at or beyond that point
+    pub(crate) fn seek_bytes(&mut self, token: &[u8]) {
+        self.reset();
+
+        let Some(root) = self.root else {
+            return
+        };
+
+        self.push(root);
+
+        let mut current: &Node<K, V, A> = root;
+        let mut nav_token: &[u8] = token;
+
+        loop {
+            // Current node's key is a prefix of token, so only its edges are candidates
+            if nav_token.is_empty() {
+                self.leftmost();
+                return
+            }
+
+            match traverse_match(current, nav_token) {
+                Some(KeyMatch {next, common: _, leftover: SuffixType::Empty, ..}) => {
+                    self.push(next);
+                    self.leftmost();
+                    return
+                },
+                Some(KeyMatch {next, common: _, leftover: SuffixType::OnlyToken(sufx), ..}) => {
+                    self.push(next);
+                    current = next;
+                    nav_token = sufx;
+                },
+                // Token ends inside the label, all keys below are greater than token
+                Some(KeyMatch {next, common: _, leftover: SuffixType::OnlyEdge(_), ..}) => {
+                    self.push(next);
+                    self.leftmost();
+                    return
+                },
+                Some(KeyMatch {next, common: _, leftover: SuffixType::BothEdgeToken(e, t), edge_key}) => {
+                    if e[0] > t[0] {
+                        self.push(next);
+                        self.leftmost();
+                    } else {
+                        self.advance(edge_key);
+                    }
+                    return
+                },
+                None => {
+                    self.advance(nav_token[0]);
+                    return
+                },
+            }
+        }
+    }
+
+    fn current(&self) -> Option<(K::Owned, &'a V)>
+    where K: TrieKey
+    {
+        self.current_bytes().map(|(k, v)| (decode_stored::<K>(k), v))
+    }
+
+    fn current_bytes(&self) -> Option<(&[u8], &'a V)> {
+        self.path.last().and_then(|n| n.value()).map(|v| (self.key.as_slice(), v))
+    }
+
+    fn reset(&mut self) {
+        self.path.clear();
+        self.key.clear();
+    }
+
+    fn push(&mut self, node: &'a Node<K, V, A>) {
+        self.path.push(node);
+        self.key.extend_from_slice(node.label().unwrap_or_default());
+    }
+
+    // Pops the last node off the path, returning its edge key
+    fn pop(&mut self) -> u8 {
+        let node = self.path.pop().unwrap();
+        let label = node.label().unwrap();
+
+        self.key.truncate(self.key.len() - label.len());
+        label[0]
+    }
+
+    // Descends to the first key found at or below the last node
+    fn leftmost(&mut self) {
+        while let Some(node) = self.path.last().copied() {
+            if node.is_key() {
+                return
+            }
+
+            match node.first_edge() {
+                Some(child) => self.push(child),
+                None => break,
+            }
+        }
+
+        self.reset()
+    }
+
+    // Descends to the last key found at or below the last node
+    fn rightmost(&mut self) {
+        while let Some(node) = self.path.last().copied() {
+            match node.last_edge() {
+                Some(child) => self.push(child),
+                None if node.is_key() => return,
+                None => break,
+            }
+        }
+
+        self.reset()
+    }
+
+    // Moves to the first key in the subtrees branching off after edge key, climbing
+    // up the path as necessary, ends at the ghost position if there is none
+    fn advance(&mut self, mut edge_key: u8) {
+        while let Some(node) = self.path.last().copied() {
+            if let Some(sibling) = node.next_edge(edge_key) {
+                self.push(sibling);
+                self.leftmost();
+                return
+            }
+
+            if self.path.len() == 1 {
+                break
+            }
+
+            edge_key = self.pop();
+        }
+
+        self.reset()
+    }
+
+    // Moves to the last key in the subtrees branching off before edge key, climbing
+    // up the path as necessary, ends at the ghost position if there is none
+    fn retreat(&mut self, mut edge_key: u8) {
+        while let Some(node) = self.path.last().copied() {
+            if let Some(sibling) = node.prev_edge(edge_key) {
+                self.push(sibling);
+                self.rightmost();
+                return
+            }
+
+            // An ancestor key precedes its descendants
+            if node.is_key() {
+                return
+            }
+
+            if self.path.len() == 1 {
+                break
+            }
+
+            edge_key = self.pop();
+        }
+
+        self.reset()
+    }
+}
+
+impl<'a, K, V, A: TrieAggregate<V>> CursorMut<'a, K, V, A> {
+    pub(crate) fn new(trie: &'a mut Trie<K, V, A>) -> Self {
+        let (root, size) = trie.take_root();
+
+        CursorMut {
+            trie,
+            root,
+            size,
+            path: vec![],
+            key: vec![],
+            ghost: true,
+            touched: vec![],
+        }
+    }
+
+    // Returns current key's mut value, recording the key for the aggregates' write-back
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        if self.ghost {
+            return None
+        }
+
+        if !A::TRIVIAL && self.touched.last() != Some(&self.key) {
+            self.touched.push(self.key.clone());
+        }
+
+        self.top_mut().and_then(|n| n.value_mut())
+    }
+
+    // Removes the current key, moving the cursor to the following key
+    pub fn remove_current(&mut self) -> Option<(K::Owned, V)>
+    where K: TrieKey
+    {
+        let key = self.key_bytes()?.to_vec();
+
+        self.next_bytes();
+        let next = self.key_bytes().map(|k| k.to_vec());
+
+        self.restore();
+        let value = self.trie.remove_bytes(&key);
+        self.retake(next);
+
+        value.map(|v| (decode_stored::<K>(&key), v))
+    }
+
+    // Inserts key and value directly after the current key (without moving the cursor),
+    // provided that key falls between the current key and the following key.
+    // Otherwise trie order would be violated, hence the value is handed back
+    pub fn insert_after(&mut self, token: K, value: V) -> Result<(), V>
+    where K: TrieKey
+    {
+        let token = token.encode();
+        let current = self.key_bytes().map(|k| k.to_vec());
+
+        self.next_bytes();
+        let after_current = current.as_deref().is_none_or(|k| k < &*token);
+        let before_next = self.key_bytes().is_none_or(|k| &*token < k);
+
+        if !after_current || !before_next {
+            self.seek_to(current);
+            return Err(value)
+        }
+
+        self.restore();
+        self.trie.insert_bytes(token, value);
+        self.retake(current);
+        Ok(())
+    }
+}
+
+impl<K, V, A> CursorMut<'_, K, V, A> {
+    pub fn key(&self) -> Option<K::Owned>
+    where K: TrieKey
+    {
+        self.key_bytes().map(decode_stored::<K>)
+    }
+
+    pub fn value(&self) -> Option<&V> {
+        self.top().filter(|_| !self.ghost).and_then(|n| n.value())
+    }
+
+    // Positions cursor at the first key greater than or equal to token
+    pub fn seek(&mut self, token: K) -> Option<(K::Owned, &V)>
+    where K: TrieKey
+    {
+        self.seek_bytes(&token.encode());
+        self.current()
+    }
+
+    // Steps to the next key in lexicographic order
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(K::Owned, &V)>
+    where K: TrieKey
+    {
+        self.next_bytes();
+        self.current()
+    }
+
+    // Steps to the previous key in lexicographic order
+    pub fn prev(&mut self) -> Option<(K::Owned, &V)>
+    where K: TrieKey
+    {
+        self.prev_bytes();
+        self.current()
+    }
+
+    fn current(&self) -> Option<(K::Owned, &V)>
+    where K: TrieKey
+    {
+        self.value().map(|v| (decode_stored::<K>(&self.key), v))
+    }
+
+    fn key_bytes(&self) -> Option<&[u8]> {
+        (!self.ghost).then_some(self.key.as_slice())
+    }
+
+    fn top(&self) -> Option<&Node<K, V, A>> {
+        self.path.last().map(|b| &**b).or(self.root.as_ref())
+    }
+
+    fn top_mut(&mut self) -> Option<&mut Node<K, V, A>> {
+        self.path.last_mut().map(|b| &mut **b).or(self.root.as_mut())
+    }
+
+    // Edge key of the top node's child picked by edge_fn, if any
+    fn edge_key<F>(&self, edge_fn: F) -> Option<u8>
+    where F: FnOnce(&Node<K, V, A>) -> Option<&Node<K, V, A>>
+    {
+        self.top().and_then(edge_fn).map(|n| n.label().unwrap()[0])
+    }
+
+    fn next_bytes(&mut self) {
+        if self.ghost {
+            if self.root.is_some() {
+                self.ghost = false;
+                self.leftmost();
+            }
+            return
+        }
+
+        // Descendants follow their ancestor key
+        match self.edge_key(|n| n.first_edge()) {
+            Some(edge_key) => {
+                self.push(edge_key);
+                self.leftmost();
+            },
+            // The root holds the empty key, with nothing after it when it has no edges
+            None if self.path.is_empty() => self.reset(),
+            None => {
+                let edge_key = self.pop();
+                self.advance(edge_key);
+            },
+        }
+    }
+
+    fn prev_bytes(&mut self) {
+        if self.ghost {
+            if self.root.is_some() {
+                self.ghost = false;
+                self.rightmost();
+            }
+            return
+        }
+
+        // Nothing precedes the root's empty key
+        if self.path.is_empty() {
+            return self.reset()
+        }
+
+        let edge_key = self.pop();
+        self.retreat(edge_key);
+    }
+
+    // Positions cursor at the first key at or beyond the point where token diverges
+    // from the trie's labels, as Cursor does
+    fn seek_bytes(&mut self, token: &[u8]) {
+        self.reset();
+
+        if self.root.is_none() {
+            return
+        }
+
+        self.ghost = false;
+        let mut nav_token: &[u8] = token;
+
+        loop {
+            // Current node's key is a prefix of token, so only its edges are candidates
+            if nav_token.is_empty() {
+                return self.leftmost()
+            }
+
+            let edge_key = nav_token[0];
+            let Some(label) = self.top().and_then(|n| n.lookup_edge(edge_key)).and_then(|c| c.label()) else {
+                return self.advance(edge_key)
+            };
+
+            let common = common_len(label, nav_token);
+
+            if common == label.len() {
+                nav_token = &nav_token[common..];
+                self.push(edge_key);
+                continue
+            }
+
+            // Token ends inside the label or diverges below it, all keys below are greater
+            if common == nav_token.len() || label[common] > nav_token[common] {
+                self.push(edge_key);
+                self.leftmost();
+            } else {
+                self.advance(edge_key);
+            }
+
+            return
+        }
+    }
+
+    fn seek_to(&mut self, key: Option<Vec<u8>>) {
+        match key {
+            Some(key) => self.seek_bytes(&key),
+            None => self.reset(),
+        }
+    }
+
+    // Detaches the top node's child at edge key, descending into it
+    fn push(&mut self, edge_key: u8) {
+        let child = self.top_mut().and_then(|n| n.detach_edge(edge_key)).unwrap();
+
+        self.key.extend_from_slice(child.label().unwrap());
+        self.path.push(child);
+    }
+
+    // Attaches the top node back onto its parent, returning its edge key
+    fn pop(&mut self) -> u8 {
+        let node = self.path.pop().unwrap();
+        let label = node.label().unwrap();
+        let edge_key = label[0];
+
+        self.key.truncate(self.key.len() - label.len());
+        self.top_mut().unwrap().attach_edge(node);
+        edge_key
+    }
+
+    fn reset(&mut self) {
+        while !self.path.is_empty() {
+            self.pop();
+        }
+
+        self.ghost = true;
+    }
+
+    // Descends to the first key found at or below the top node
+    fn leftmost(&mut self) {
+        while let Some(node) = self.top() {
+            if node.is_key() {
+                return
+            }
+
+            match self.edge_key(|n| n.first_edge()) {
+                Some(edge_key) => self.push(edge_key),
+                None => break,
+            }
+        }
+
+        self.reset()
+    }
+
+    // Descends to the last key found at or below the top node
+    fn rightmost(&mut self) {
+        while let Some(node) = self.top() {
+            let is_key = node.is_key();
+
+            match self.edge_key(|n| n.last_edge()) {
+                Some(edge_key) => self.push(edge_key),
+                None if is_key => return,
+                None => break,
+            }
+        }
+
+        self.reset()
+    }
+
+    // Moves to the first key in the subtrees branching off after edge key, climbing
+    // up the path as necessary, ends at the ghost position if there is none
+    fn advance(&mut self, mut edge_key: u8) {
+        loop {
+            if let Some(sibling) = self.edge_key(|n| n.next_edge(edge_key)) {
+                self.push(sibling);
+                return self.leftmost()
+            }
+
+            if self.path.is_empty() {
+                break
+            }
+
+            edge_key = self.pop();
+        }
+
+        self.reset()
+    }
+
+    // Moves to the last key in the subtrees branching off before edge key, climbing
+    // up the path as necessary, ends at the ghost position if there is none
+    fn retreat(&mut self, mut edge_key: u8) {
+        loop {
+            if let Some(sibling) = self.edge_key(|n| n.prev_edge(edge_key)) {
+                self.push(sibling);
+                return self.rightmost()
+            }
+
+            // An ancestor key precedes its descendants
+            if self.top().is_some_and(|n| n.is_key()) {
+                return
+            }
+
+            if self.path.is_empty() {
+                break
+            }
+
+            edge_key = self.pop();
+        }
+
+        self.reset()
+    }
+
+    // Attaches the path back and hands the root back to the trie, unless it already was
+    fn restore(&mut self) {
+        self.reset();
+
+        if let Some(root) = self.root.take() {
+            self.trie.restore_root(root, self.size, mem::take(&mut self.touched));
+        }
+    }
+
+    // Takes the root back from the trie after a structural edit, descending to key again
+    fn retake(&mut self, key: Option<Vec<u8>>) {
+        (self.root, self.size) = self.trie.take_root();
+        self.seek_to(key);
+    }
+}
+
+impl<K, V, A> Drop for CursorMut<'_, K, V, A> {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+// Cursor unit tests
+
+#[cfg(test)]
+mod tests {
+    use crate::trie::Trie;
+
+    fn str_helper<V: Copy>(item: Option<(String, &V)>) -> Option<(String, V)> {
+        item.map(|(k, v)| (k, *v))
+    }
+
+    fn pair(k: &str, v: i32) -> Option<(String, i32)> {
+        Some((k.to_string(), v))
+    }
+
+    #[test]
+    fn check_cursor_steps() {
+        let trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77), ("ant", 3)].iter().cloned().collect();
+        let mut cursor = trie.cursor();
+
+        assert_eq!(None, cursor.key());
+        assert_eq!(pair("and", 77), str_helper(cursor.next()));
+        assert_eq!(pair("ant", 3), str_helper(cursor.next()));
+        assert_eq!(pair("anthem", 1), str_helper(cursor.next()));
+        assert_eq!(pair("anthemion", 7), str_helper(cursor.next()));
+        assert_eq!(pair("anti", 2), str_helper(cursor.next()));
+        assert_eq!(None, str_helper(cursor.next()));
+
+        // Wraps around from the ghost position
+        assert_eq!(pair("anti", 2), str_helper(cursor.prev()));
+        assert_eq!(pair("anthemion", 7), str_helper(cursor.prev()));
+        assert_eq!(pair("anthem", 1), str_helper(cursor.prev()));
+        assert_eq!(pair("ant", 3), str_helper(cursor.prev()));
+        assert_eq!(pair("and", 77), str_helper(cursor.prev()));
+        assert_eq!(None, str_helper(cursor.prev()));
+    }
+
+    #[test]
+    fn check_cursor_seek() {
+        let trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77), ("ant", 3)].iter().cloned().collect();
+        let mut cursor = trie.cursor();
+
+        assert_eq!(pair("anthem", 1), str_helper(cursor.seek("anthem")));
+        assert_eq!(pair("anthem", 1), str_helper(cursor.seek("anth")));
+        assert_eq!(pair("anthemion", 7), str_helper(cursor.seek("anthema")));
+        assert_eq!(pair("anti", 2), str_helper(cursor.seek("anthz")));
+        assert_eq!(pair("and", 77), str_helper(cursor.seek("a")));
+        assert_eq!(pair("and", 77), str_helper(cursor.seek("")));
+        assert_eq!(None, str_helper(cursor.seek("antz")));
+        assert_eq!(&1, cursor.seek("anthe").map(|_| ()).and(cursor.value()).unwrap());
+        assert_eq!(pair("ant", 3), str_helper(cursor.prev()));
+        assert_eq!(None, Trie::<&str, i32>::new().cursor().seek("a"));
+    }
+
+    #[test]
+    fn check_cursor_mut() {
+        let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("and", 77)].iter().cloned().collect();
+        let mut cursor = trie.cursor_mut();
+
+        assert_eq!(Some(("anthem".to_string(), &1)), cursor.seek("ant"));
+        *cursor.value_mut().unwrap() += 10;
+
+        assert_eq!(Err(5), cursor.insert_after("antz", 5));
+        assert_eq!(Ok(()), cursor.insert_after("anthemion", 7));
+        assert_eq!(Some("anthem".to_string()), cursor.key());
+
+        assert_eq!(Some(("anthem".to_string(), 11)), cursor.remove_current());
+        assert_eq!(Some("anthemion".to_string()), cursor.key());
+        assert_eq!(Some(&2), cursor.next().map(|(_, v)| v));
+        assert_eq!(None, cursor.next());
+        assert_eq!(Some(&77), cursor.next().map(|(_, v)| v));
+
+        // The trie gets its nodes back once the cursor is dropped
+        drop(cursor);
+        assert_eq!(3, trie.len());
+        assert_eq!(&7, trie.search("anthemion").unwrap());
+
+        // From the ghost position the empty key goes ahead of every other key
+        assert_eq!(Ok(()), trie.cursor_mut().insert_after("", 0));
+        assert_eq!(Some(("".to_string(), &0)), trie.first_key_value());
+    }
+}
@@ -53,7 +53,7 @@ pub enum Action {
 
 // (Rust supports recursion yet not tail recursion - the explicit stack is on the heap
 // so it avoids  concerns of potentially blowing the call stack for long sequences)
-pub fn capture<K, V>(current: &Node<K, V>, prefix: &[u8]) -> Option<DeletePlan> {
+pub fn capture<K, V, A>(current: &Node<K, V, A>, prefix: &[u8]) -> Option<DeletePlan> {
     let mut replay: Vec<Playback> = Vec::new();
     let mut status: HashSet<Status> = HashSet::new();
     let mut action: Action = Action::Noop;
@@ -64,7 +64,7 @@ pub fn capture<K, V>(current: &Node<K, V>, prefix: &[u8]) -> Option<DeletePlan>
     // Take the dfs stack (with the terminal node on top)
     // and convert it into a replay stack
 
-    let result: TraverseResult<K, V> =  traverse(current, prefix, TraverseType::Fold)?;
+    let result: TraverseResult<K, V, A> =  traverse(current, prefix, TraverseType::Fold)?;
     let mut stack = enum_extract!(result, TraverseResult::Stack);
 
     // Prepopulated stack given prefix and trie
@@ -94,8 +94,7 @@ pub fn capture<K, V>(current: &Node<K, V>, prefix: &[u8]) -> Option<DeletePlan>
     }
 
     // Work backwards from the node we want to delete
-    while !stack.is_empty() {
-        let TraverseItem{node, next_key, label: _, level} = stack.pop().unwrap();
+    while let Some(TraverseItem{node, next_key, label: _, level}) = stack.pop() {
 
         match action {
             Action::Prune => {
@@ -133,9 +132,11 @@ pub fn capture<K, V>(current: &Node<K, V>, prefix: &[u8]) -> Option<DeletePlan>
         }
 
         // A passthrough node is able to be compressed only after a single prune
+        // (the root node has no label hence is never a passthrough node)
         if action == Action::Prune &&
             status.contains(&Status::DeletedPruned) && status.len() == 1 &&
-            !node.is_key() && node.edge_type().unwrap() == EdgeType::Branching(2) {
+            !node.is_key() && node.label().is_some() &&
+            node.edge_type().unwrap() == EdgeType::Branching(2) {
 
                 // Record key that will be used as the merge key / merge node
                 // when we merge the passthrough node's label with the merge node
@@ -172,7 +173,7 @@ mod tests {
     fn check_delete_plan() {
         let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().rev().cloned().collect();
 
-        let result = vec!["anthemion", "anthem", "and", "anti"];
+        let result = ["anthemion", "anthem", "and", "anti"];
 
         let mut i = 0;
 
@@ -183,7 +184,7 @@ mod tests {
         assert_eq!(pb, vec![P::Unmark(C::Node(4)), P::Prune(C::Link(3, 105)),
                             P::Keep(C::Link(2, 104)), P::Keep(C::Link(1, 116)), P::Keep(C::Link(0, 97))]);
 
-        trie.remove(&result[i]);
+        trie.remove(result[i]);
         i+=1;
 
         let root = trie.root();
@@ -192,7 +193,7 @@ mod tests {
         // anthem
         assert_eq!(pb, vec![P::Unmark(C::Node(3)), P::Prune(C::Link(2, 104)), P::Merge(C::DoubleLink(1, 116, 105)), P::Keep(C::Link(0, 97))]);
 
-        trie.remove(&result[i]);
+        trie.remove(result[i]);
         i+=1;
 
         let root = trie.root();
@@ -201,7 +202,7 @@ mod tests {
         // and
         assert_eq!(pb, vec![P::Unmark(C::Node(2)), P::Prune(C::Link(1, 100)), P::Merge(C::DoubleLink(0, 97, 116))]);
 
-        trie.remove(&result[i]);
+        trie.remove(result[i]);
         i+=1;
 
         let root = trie.root();
@@ -210,7 +211,7 @@ mod tests {
         // anti
         assert_eq!(pb, vec![P::Unmark(C::Node(1)), P::Prune(C::Link(0, 97))]);
 
-        trie.remove(&result[i]);
+        trie.remove(result[i]);
 
         assert!(trie.is_empty());
     }
@@ -0,0 +1,220 @@
+use crate::trie::Trie;
+use crate::key::encode_element;
+
+// A trie for suffix matching domain names, keys are stored with their labels reversed
+// ("api.example.com" as com, example, api) so the zones a domain falls under are prefixes
+// of its key. Labels are stored as escaped and terminated elements, hence "ample.com" never
+// matches "example.com". Names are compared case insensitively, ignoring any trailing dot
+
+// An entry for "example.com" covers example.com along with every name beneath it, while a
+// wildcard entry "*.example.com" covers only the names strictly beneath it. Both may be stored,
+// in which case the wildcard is the more specific match for subdomains. The root patterns
+// "." and "*" encode to the empty key
+
+#[derive(Clone, Debug)]
+pub struct DomainTrie<V> {
+    trie: Trie<Vec<u8>, Entry<V>>,
+    size: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Entry<V> {
+    zone: Option<(String, V)>,     // pattern as inserted along with value
+    wildcard: Option<(String, V)>,
+}
+
+impl<V> Entry<V> {
+    fn new() -> Self {
+        Entry { zone: None, wildcard: None }
+    }
+
+    fn slot(&mut self, wildcard: bool) -> &mut Option<(String, V)> {
+        if wildcard { &mut self.wildcard } else { &mut self.zone }
+    }
+
+    // Stored patterns matching a name, general first, strict if the name lies beneath the entry
+    fn matches(&self, strict: bool) -> impl Iterator<Item = (&'_ str, &'_ V)> {
+        self.zone.iter().chain(self.wildcard.iter().filter(move |_| strict))
+            .map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+// Splits off a leading wildcard and encodes the remaining labels in reverse
+fn encode(pattern: &str) -> (Vec<u8>, bool) {
+    let pattern = pattern.strip_suffix('.').unwrap_or(pattern);
+    let (name, wildcard) = match pattern.strip_prefix('*') {
+        Some(rest) if rest.is_empty() || rest.starts_with('.') => (rest, true),
+        _ => (pattern, false),
+    };
+
+    let mut bytes = Vec::with_capacity(name.len() + 2);
+
+    for label in name.rsplit('.').filter(|l| !l.is_empty()) {
+        encode_element(&mut bytes, label.to_ascii_lowercase().as_bytes());
+    }
+
+    (bytes, wildcard)
+}
+
+impl<V> DomainTrie<V> {
+    pub fn new() -> Self {
+        DomainTrie {
+            trie: Trie::new(),
+            size: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    // Inserts a zone e.g. "example.com" or a wildcard e.g. "*.example.com",
+    // returning the value previously stored for the pattern
+    pub fn insert(&mut self, pattern: &str, value: V) -> Option<V> {
+        let (key, wildcard) = encode(pattern);
+        let entry = self.trie.get_or_insert_with_bytes(&key, Entry::new);
+        let old = entry.slot(wildcard).replace((pattern.to_string(), value)).map(|(_, v)| v);
+
+        if old.is_none() {
+            self.size += 1;
+        }
+
+        old
+    }
+
+    // Retrieves the value stored for exactly the pattern
+    pub fn get(&self, pattern: &str) -> Option<&'_ V> {
+        let (key, wildcard) = encode(pattern);
+        let entry = self.trie.search(key)?;
+
+        if wildcard { entry.wildcard.as_ref() } else { entry.zone.as_ref() }.map(|(_, v)| v)
+    }
+
+    pub fn remove(&mut self, pattern: &str) -> Option<V> {
+        let (key, wildcard) = encode(pattern);
+
+        let entry = self.trie.search_mut_bytes(&key)?;
+        let old = entry.slot(wildcard).take().map(|(_, v)| v);
+
+        // A stored key keeps at least one of its patterns
+        if entry.zone.is_none() && entry.wildcard.is_none() {
+            self.trie.remove(key);
+        }
+
+        if old.is_some() {
+            self.size -= 1;
+        }
+
+        old
+    }
+
+    // Finds the most specific stored pattern covering domain
+    pub fn longest_suffix_match(&self, domain: &str) -> Option<(&'_ str, &'_ V)> {
+        self.all_suffixes_of(domain).last()
+    }
+
+    // Iterates all stored patterns covering domain, least specific first
+    pub fn all_suffixes_of(&self, domain: &str) -> impl Iterator<Item = (&'_ str, &'_ V)> {
+        let (key, _) = encode(domain);
+        let len = key.len();
+
+        self.trie.prefixes_of(key).flat_map(move |(n, entry)| entry.matches(n < len))
+    }
+
+    // Iterates all stored patterns and values, ordered by reversed labels
+    pub fn iter(&self) -> impl Iterator<Item = (&'_ str, &'_ V)> {
+        self.trie.values().flat_map(|entry| entry.matches(true))
+    }
+
+    pub fn clear(&mut self) {
+        self.trie.clear();
+        self.size = 0;
+    }
+}
+
+impl<V> Default for DomainTrie<V> {
+    fn default() -> Self {
+        DomainTrie::new()
+    }
+}
+
+// DomainTrie unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns_helper<'a>(iter: impl Iterator<Item = (&'a str, &'a i32)>) -> Vec<&'a str> {
+        iter.map(|(k, _)| k).collect()
+    }
+
+    #[test]
+    fn check_suffix_match() {
+        let mut trie = DomainTrie::new();
+
+        trie.insert("example.com", 1);
+        trie.insert("eu.example.com.", 2);
+        trie.insert("*.eu.example.com", 3);
+        trie.insert("ample.com", 4);
+        trie.insert("*.org", 5);
+        assert_eq!(5, trie.len());
+
+        assert_eq!(Some(("*.eu.example.com", &3)), trie.longest_suffix_match("api.EU.example.com"));
+        assert_eq!(Some(("eu.example.com.", &2)), trie.longest_suffix_match("eu.example.com"));
+        assert_eq!(Some(("example.com", &1)), trie.longest_suffix_match("www.example.com"));
+        assert_eq!(None, trie.longest_suffix_match("www.sample.com"));
+        assert_eq!(Some(("ample.com", &4)), trie.longest_suffix_match("ample.com"));
+
+        // wildcards cover only strict subdomains
+        assert_eq!(None, trie.longest_suffix_match("org"));
+        assert_eq!(Some(("*.org", &5)), trie.longest_suffix_match("rust-lang.org"));
+
+        assert_eq!(vec!["example.com", "eu.example.com.", "*.eu.example.com"],
+                   patterns_helper(trie.all_suffixes_of("a.b.eu.example.com")));
+    }
+
+    #[test]
+    fn check_domain_remove() {
+        let mut trie = DomainTrie::new();
+
+        trie.insert("example.com", 1);
+        trie.insert("*.example.com", 2);
+
+        assert_eq!(Some(&2), trie.get("*.Example.com"));
+        assert_eq!(Some(1), trie.remove("example.com"));
+        assert_eq!(None, trie.longest_suffix_match("example.com"));
+        assert_eq!(Some(("*.example.com", &2)), trie.longest_suffix_match("www.example.com"));
+
+        assert_eq!(Some(2), trie.insert("*.example.com", 3));
+        assert_eq!(Some(3), trie.remove("*.example.com"));
+        assert!(trie.is_empty());
+        assert_eq!(0, trie.iter().count());
+    }
+
+    #[test]
+    fn check_root_patterns() {
+        let mut trie = DomainTrie::new();
+
+        trie.insert("*", 1);
+        trie.insert("example.com", 2);
+        assert_eq!(2, trie.len());
+        assert_eq!(Some(&1), trie.get("*"));
+        assert_eq!(None, trie.get("."));
+
+        assert_eq!(Some(("*", &1)), trie.longest_suffix_match("a.com"));
+        assert_eq!(None, trie.longest_suffix_match("."));
+        assert_eq!(vec!["*", "example.com"], patterns_helper(trie.all_suffixes_of("www.example.com")));
+
+        assert_eq!(None, trie.insert(".", 3));
+        assert_eq!(Some((".", &3)), trie.longest_suffix_match(""));
+        assert_eq!(vec![".", "*", "example.com"], patterns_helper(trie.iter()));
+
+        assert_eq!(Some(1), trie.remove("*"));
+        assert_eq!(Some((".", &3)), trie.longest_suffix_match("a.com"));
+        assert_eq!(2, trie.len());
+    }
+}
@@ -1,28 +1,48 @@
 #![allow(dead_code)]
 
-use crate::node::Node;
+use std::collections::VecDeque;
+
+use crate::node::{Node, KeyedNode};
 use crate::macros::enum_extract;
+use crate::key::{TrieKey, decode_stored};
 
 // Iteration types are implemented as new types (kudos Haskell)
 // around a base iter type
 
 #[derive(Clone, Debug)]
-pub struct LabelsIter<'a, K, V>(BaseIter<'a, K, V>);
+pub struct LabelsIter<'a, K, V, A = ()>(BaseIter<'a, K, V, A>);
 
 #[derive(Clone, Debug)]
-pub struct ValuesIter<'a, K, V>(BaseIter<'a, K, V>);
+pub struct ValuesIter<'a, K, V, A = ()>(BaseIter<'a, K, V, A>);
 
 #[derive(Debug)]
-pub struct ValuesIterMut<'a, K, V>(BaseIterMut<'a, K, V>);
+pub struct ValuesIterMut<'a, K, V, A = ()>(BaseIterMut<'a, K, V, A>);
 
 #[derive(Clone, Debug)]
-pub struct LeafPairsIter<'a, K, V>(BaseIter<'a, K, V>);
+pub struct LeafPairsIter<'a, K, V, A = ()>(BaseIter<'a, K, V, A>);
 
 #[derive(Debug)]
-pub struct LeafPairsIterMut<'a, K, V>(BaseIterMut<'a, K, V>);
+pub struct LeafPairsIterMut<'a, K, V, A = ()>(BaseIterMut<'a, K, V, A>);
+
+#[derive(Clone, Debug)]
+pub struct IntoIter<K, V, A = ()>(BaseIterOwned<K, V, A>);
+
+#[derive(Clone, Debug)]
+pub struct KeysIter<'a, K, V, A = ()>(BaseIterKeyed<'a, K, V, A>);
+
+#[derive(Clone, Debug)]
+pub struct PairsIter<'a, K, V, A = ()>(BaseIterKeyed<'a, K, V, A>);
+
+// Typed iterators decode each key's bytes back into the key type's owned form
+
+#[derive(Clone, Debug)]
+pub struct TypedKeysIter<'a, K, V, A = ()>(KeysIter<'a, K, V, A>);
+
+#[derive(Clone, Debug)]
+pub struct TypedPairsIter<'a, K, V, A = ()>(PairsIter<'a, K, V, A>);
 
 #[derive(Clone, Debug)]
-pub struct IntoIter<K, V>(BaseIterOwned<K, V>);
+pub struct TypedIntoIter<K, V, A = ()>(IntoIter<K, V, A>);
 
 #[derive(Copy, Clone, Debug)]
 enum IterationType {
@@ -31,7 +51,18 @@ enum IterationType {
     ValuesMut,
     LabelsValues,
     LabelsValuesMut,
-    ValuesOwned,
+    PairsOwned,
+    Keys,
+    KeysValues,
+}
+
+impl IterationType {
+    // Whether each item yielded corresponds to exactly one key, only then is the
+    // remaining size accurate enough to tell when front and back iteration meet
+    #[inline]
+    fn is_counted(self) -> bool {
+        !matches!(self, IterationType::Labels)
+    }
 }
 
 #[derive(Debug)]
@@ -40,54 +71,93 @@ enum NextType<'a, V> {
     // LabelsMutRef not supported as would violate Trie integrity just like HashMap, BTreeMap, etc..
     ValueRef(Option<&'a V>),
     ValueRefMut(Option<&'a mut V>),
-    ValueOwned(Option<V>),
+    PairOwned(Option<(Vec<u8>, V)>),
     LeafPairRef(Option<(&'a [u8], &'a V)>),
     LeafPairRefMut(Option<(&'a [u8], &'a mut V)>),
+    Key(Option<Vec<u8>>),
+    KeyValue(Option<(Vec<u8>, &'a V)>),
 }
 
 /*-----------------------------------------------------------------------*/
-// Handles DFS iteration using a stack and total size
+// Handles DFS iteration using a stack and total size, the back stack handles
+// the reverse iteration with each node flagged once its edges have been expanded
 #[derive(Clone, Debug)]
-pub struct BaseIter<'a, K, V> {
-    stack: Vec<&'a Node<K, V>>,
+pub struct BaseIter<'a, K, V, A> {
+    stack: Vec<&'a Node<K, V, A>>,
+    back: Vec<(&'a Node<K, V, A>, bool)>,
     size: usize,
 }
 
-// Handles DFS mut iteration using a stack and total size
+// Handles DFS mut iteration from both ends using a deque of pending entries kept in key order
+// and total size. A node can't be shared between a front and a back stack, hence a popped node
+// is replaced in place by its own value followed by its edges
 #[derive(Debug)]
-pub struct BaseIterMut<'a, K, V> {
-    stack: Vec<&'a mut Node<K, V>>,
+pub struct BaseIterMut<'a, K, V, A> {
+    deque: VecDeque<EntryMut<'a, K, V, A>>,
     size: usize,
 }
 
-// Handles DFS iteration by value using a stack and total size
+#[derive(Debug)]
+enum EntryMut<'a, K, V, A> {
+    Node(&'a mut Node<K, V, A>),
+    Value(Option<&'a [u8]>, &'a mut V),
+}
+
+// Handles DFS iteration by value from both ends using a deque (see BaseIterMut), where each
+// node is paired with its full key reassembled from the labels along its path
+#[derive(Clone, Debug)]
+pub struct BaseIterOwned<K, V, A> {
+    deque: VecDeque<EntryOwned<K, V, A>>,
+}
+
+#[derive(Clone, Debug)]
+enum EntryOwned<K, V, A> {
+    Node(Node<K, V, A>, Vec<u8>),
+    Pair(Vec<u8>, V),
+}
+
+// Handles DFS iteration using a stack and total size, where each stacked node
+// is paired with its full key reassembled from the labels along its path
 #[derive(Clone, Debug)]
-pub struct BaseIterOwned<K, V> {
-    stack: Vec<Node<K, V>>,
+pub struct BaseIterKeyed<'a, K, V, A> {
+    stack: Vec<KeyedNode<'a, K, V, A>>,
+    back: Vec<(KeyedNode<'a, K, V, A>, bool)>,
+    size: usize,
 }
 
-impl<'a, K: 'a, V: 'a> Default for BaseIter<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: 'a> Default for BaseIter<'a, K, V, A> {
     fn default() -> Self {
         BaseIter {
             stack: vec![],
+            back: vec![],
             size: 0,
         }
     }
 }
 
-impl<'a, K: 'a, V: 'a> Default for BaseIterMut<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: 'a> Default for BaseIterMut<'a, K, V, A> {
     fn default() -> Self {
         BaseIterMut {
+            deque: VecDeque::new(),
+            size: 0,
+        }
+    }
+}
+
+impl<'a, K: 'a, V: 'a, A: 'a> Default for BaseIterKeyed<'a, K, V, A> {
+    fn default() -> Self {
+        BaseIterKeyed {
             stack: vec![],
+            back: vec![],
             size: 0,
         }
     }
 }
 
-impl<K, V> Default for BaseIterOwned<K, V> {
+impl<K, V, A> Default for BaseIterOwned<K, V, A> {
     fn default() -> Self {
         BaseIterOwned {
-            stack: vec![],
+            deque: VecDeque::new(),
         }
     }
 }
@@ -95,50 +165,88 @@ impl<K, V> Default for BaseIterOwned<K, V> {
 //-----------------------------------------------------------------------
 // BaseIter methods
 
-impl<'a, K: 'a, V: 'a> BaseIter<'a, K, V> {
-    pub fn new(node: &'a Node<K, V>, size: usize) -> BaseIter<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: 'a> BaseIter<'a, K, V, A> {
+    pub fn new(node: &'a Node<K, V, A>, size: usize) -> BaseIter<'a, K, V, A> {
         BaseIter {
             stack: vec![node],
+            back: vec![(node, false)],
             size,
         }
     }
 
     // Next method leverages vector's extend trait implementation to add an entire iteration
     // of outgoing edge nodes instead of having to handle the case of specific item or iter
+    // (edges are ordered and pushed in reverse, so nodes are popped in lexicographic order)
     fn next(&mut self, itype: IterationType) -> Option<NextType<'a, V>> {
         loop {
+            if itype.is_counted() && self.size == 0 {
+                break None
+            }
+
             match self.stack.pop() {
                 None => break None,
                 Some(n) => {
                     let view = n.node_view();
-                    self.stack.extend(view.edges.map(|b| &**b));
+                    self.stack.extend(view.edges.rev().map(|b| &**b));
 
-                    match itype {
-                        IterationType::Labels => {
-                            // Don't add root label which is none
-                            if view.label.is_some() {
-                                break Some(NextType::LabelRef(view.label))
-                            }
-                        },
-                        IterationType::Values => {
-                            // Only pass data that have actual value
-                            if view.value.is_some() {
-                                break Some(NextType::ValueRef(view.value))
-                            }
-                        },
-                        IterationType::LabelsValues => {
-                            // Pass leaf data that has a label and a value
-                            if view.label.is_some() && view.value.is_some() {
-                                break Some(NextType::LeafPairRef(Some((view.label.unwrap(), view.value.unwrap()))))
-                            }
-                        },
-                        _ => unreachable!()
+                    if let Some(item) = self.select(view.label, view.value, itype) {
+                        break Some(item)
+                    }
+                },
+            }
+        }
+    }
+
+    // Next back visits nodes in reverse lexicographic order, a node's edges are expanded
+    // in order (the last edge ends up on top) and the node itself is only selected
+    // once all of its descendants have been visited
+    fn next_back(&mut self, itype: IterationType) -> Option<NextType<'a, V>> {
+        loop {
+            if self.size == 0 {
+                break None
+            }
+
+            match self.back.pop() {
+                None => break None,
+                Some((n, false)) => {
+                    self.back.push((n, true));
+                    self.back.extend(n.node_view().edges.map(|b| (&**b, false)));
+                },
+                Some((n, true)) => {
+                    let view = n.node_view();
+
+                    if let Some(item) = self.select(view.label, view.value, itype) {
+                        break Some(item)
                     }
                 },
             }
         }
     }
 
+    fn select(&mut self, label: Option<&'a [u8]>, value: Option<&'a V>, itype: IterationType) -> Option<NextType<'a, V>> {
+        let item = match itype {
+            IterationType::Labels => {
+                // Don't add root label which is none
+                label.map(|_| NextType::LabelRef(label))
+            },
+            IterationType::Values => {
+                // Only pass data that have actual value
+                value.map(|_| NextType::ValueRef(value))
+            },
+            IterationType::LabelsValues => {
+                // Pass leaf data that has a value, the root's empty key has an empty label
+                value.map(|v| NextType::LeafPairRef(Some((label.unwrap_or_default(), v))))
+            },
+            _ => unreachable!()
+        };
+
+        if item.is_some() && itype.is_counted() {
+            self.size -= 1;
+        }
+
+        item
+    }
+
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.size, Some(self.size))
@@ -147,39 +255,45 @@ impl<'a, K: 'a, V: 'a> BaseIter<'a, K, V> {
 
 /*-----------------------------------------------------------------------*/
 // Handle mut dfs iterations
-impl<'a, K: 'a, V: 'a> BaseIterMut<'a, K, V> {
-    pub fn new(node: &'a mut Node<K, V>, size: usize) -> BaseIterMut<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: 'a> BaseIterMut<'a, K, V, A> {
+    pub fn new(node: &'a mut Node<K, V, A>, size: usize) -> BaseIterMut<'a, K, V, A> {
         BaseIterMut {
-            stack: vec![node],
+            deque: VecDeque::from([EntryMut::Node(node)]),
             size,
         }
     }
 
-    // Next method leverages vector's extend trait implementation to add an entire iteration
-    // of outgoing edge nodes instead of having to handle the case of specific item or iter
-    fn next(&mut self, itype: IterationType) -> Option<NextType<'a, V>> {
+    // Pops entries from either end, expanding nodes until a selected value turns up.
+    // A node's key precedes its descendants' keys, so its value goes ahead of its edges
+    fn next(&mut self, itype: IterationType, back: bool) -> Option<NextType<'a, V>> {
         loop {
-            match self.stack.pop() {
-                None => break None,
-                Some(n) => {
+            let entry = if back { self.deque.pop_back() } else { self.deque.pop_front() };
+
+            match entry? {
+                EntryMut::Node(n) => {
                     // Mutable view type w/ accesible fields avoids concerns about exclusive mutable access to node
                     let view_mut = n.node_view_mut();
-                    self.stack.extend(view_mut.edges.map(|b| &mut **b));
-
-                    match itype {
-                        IterationType::ValuesMut => {
-                            if view_mut.value.is_some() {
-                                break Some(NextType::ValueRefMut(view_mut.value))
-                            }
-                        },
-                        IterationType::LabelsValuesMut => {
-                            // Pass leaf data that has a label and a value
-                            // Supply both ref label, ref mut value
-                            if view_mut.label.is_some() && view_mut.value.is_some() {
-                                break Some(NextType::LeafPairRefMut(Some((view_mut.label.unwrap(), view_mut.value.unwrap()))))
-                            }
-                        },
+                    let label = view_mut.label;
+                    let value = view_mut.value.map(|v| EntryMut::Value(label, v));
+                    let entries = value.into_iter().chain(view_mut.edges.map(|b| EntryMut::Node(&mut **b)));
+
+                    if back {
+                        self.deque.extend(entries);
+                    } else {
+                        entries.rev().for_each(|e| self.deque.push_front(e));
+                    }
+                },
+                EntryMut::Value(label, value) => {
+                    let item = match itype {
+                        IterationType::ValuesMut => Some(NextType::ValueRefMut(Some(value))),
+                        // Supply both ref label, ref mut value, the root's empty key has an empty label
+                        IterationType::LabelsValuesMut => Some(NextType::LeafPairRefMut(Some((label.unwrap_or_default(), value)))),
                         _ => unreachable!()
+                    };
+
+                    if item.is_some() {
+                        self.size -= 1;
+                        break item
                     }
                 },
             }
@@ -193,33 +307,141 @@ impl<'a, K: 'a, V: 'a> BaseIterMut<'a, K, V> {
 }
 
 
+/*-----------------------------------------------------------------------*/
+// Handle dfs iterations which reassemble full keys
+impl<'a, K: 'a, V: 'a, A: 'a> BaseIterKeyed<'a, K, V, A> {
+    pub fn new(node: &'a Node<K, V, A>, size: usize) -> BaseIterKeyed<'a, K, V, A> {
+        let bytes = node.label().map_or_else(Vec::new, |l| l.to_vec());
+        BaseIterKeyed::with_key(node, bytes, size)
+    }
+
+    // Seeds the iteration with node's key bytes e.g. when node's key isn't
+    // simply node's label as node isn't a child of root
+    pub fn with_key(node: &'a Node<K, V, A>, bytes: Vec<u8>, size: usize) -> BaseIterKeyed<'a, K, V, A> {
+        BaseIterKeyed {
+            stack: vec![(node, bytes.clone())],
+            back: vec![((node, bytes), false)],
+            size,
+        }
+    }
+
+    // Each child's key is its parent's key extended by the child's label,
+    // so the prefix bytes are cloned for every outgoing edge (much like all_keys)
+    // (edges are ordered and pushed in reverse, so nodes are popped in lexicographic order)
+    fn next(&mut self, itype: IterationType) -> Option<NextType<'a, V>> {
+        loop {
+            if self.size == 0 {
+                break None
+            }
+
+            match self.stack.pop() {
+                None => break None,
+                Some((n, bytes)) => {
+                    let view = n.node_view();
+
+                    self.stack.extend(view.edges.rev().map(|b| {
+                        let mut child_bytes = bytes.clone();
+                        child_bytes.extend_from_slice(b.label().unwrap());
+                        (&**b, child_bytes)
+                    }));
+
+                    if let Some(item) = self.select(bytes, view.value, itype) {
+                        break Some(item)
+                    }
+                },
+            }
+        }
+    }
+
+    // Next back visits nodes in reverse lexicographic order, see BaseIter
+    fn next_back(&mut self, itype: IterationType) -> Option<NextType<'a, V>> {
+        loop {
+            if self.size == 0 {
+                break None
+            }
+
+            match self.back.pop() {
+                None => break None,
+                Some(((n, bytes), false)) => {
+                    let view = n.node_view();
+
+                    let children = view.edges.map(|b| {
+                        let mut child_bytes = bytes.clone();
+                        child_bytes.extend_from_slice(b.label().unwrap());
+                        ((&**b, child_bytes), false)
+                    }).collect::<Vec<_>>();
+
+                    self.back.push(((n, bytes), true));
+                    self.back.extend(children);
+                },
+                Some(((n, bytes), true)) => {
+                    if let Some(item) = self.select(bytes, n.value(), itype) {
+                        break Some(item)
+                    }
+                },
+            }
+        }
+    }
+
+    fn select(&mut self, bytes: Vec<u8>, value: Option<&'a V>, itype: IterationType) -> Option<NextType<'a, V>> {
+        let item = match itype {
+            IterationType::Keys => value.map(|_| NextType::Key(Some(bytes))),
+            IterationType::KeysValues => value.map(|v| NextType::KeyValue(Some((bytes, v)))),
+            _ => unreachable!()
+        };
+
+        if item.is_some() {
+            self.size -= 1;
+        }
+
+        item
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+}
 
 //-----------------------------------------------------------------------
 // BaseIter methods
 
-impl<K, V> BaseIterOwned<K, V> {
-    pub fn new(node: Node<K, V>) -> BaseIterOwned<K, V> {
+impl<K, V, A> BaseIterOwned<K, V, A> {
+    // Bytes holds node's full key which may extend beyond node's label
+    // should the node have been detached from a larger trie
+    pub fn new(node: Node<K, V, A>, bytes: Vec<u8>) -> BaseIterOwned<K, V, A> {
         BaseIterOwned {
-            stack: vec![node],
+            deque: VecDeque::from([EntryOwned::Node(node, bytes)]),
         }
     }
 
-    // Next method leverages vector's extend trait implementation to add an entire iteration
-    // of outgoing edge nodes instead of having to handle the case of specific item or iter
-    fn next(&mut self, itype: IterationType) -> Option<NextType<V>> {
+    // Pops entries from either end, expanding nodes until a pair turns up (see BaseIterMut)
+    fn next(&mut self, itype: IterationType, back: bool) -> Option<NextType<'_, V>> {
         loop {
-            match self.stack.pop() {
-                None => break None,
-                Some(n) => {
+            let entry = if back { self.deque.pop_back() } else { self.deque.pop_front() };
+
+            match entry? {
+                EntryOwned::Node(n, bytes) => {
                     let view_owned = n.node_view_owned();
-                    self.stack.extend(view_owned.edges.map(|b| *b));
 
+                    let children = view_owned.edges.map(|b| {
+                        let mut child_bytes = bytes.clone();
+                        child_bytes.extend_from_slice(b.label().unwrap());
+                        EntryOwned::Node(*b, child_bytes)
+                    }).collect::<Vec<_>>();
+
+                    let value = view_owned.value.map(|v| EntryOwned::Pair(bytes, v));
+                    let entries = value.into_iter().chain(children);
+
+                    if back {
+                        self.deque.extend(entries);
+                    } else {
+                        entries.rev().for_each(|e| self.deque.push_front(e));
+                    }
+                },
+                EntryOwned::Pair(bytes, value) => {
                     match itype {
-                        IterationType::ValuesOwned => {
-                            if view_owned.value.is_some() {
-                                break Some(NextType::ValueOwned(view_owned.value))
-                            }
-                        },
+                        IterationType::PairsOwned => break Some(NextType::PairOwned(Some((bytes, value)))),
                         _ => unreachable!()
                     }
                 },
@@ -232,7 +454,7 @@ impl<K, V> BaseIterOwned<K, V> {
 // Macro to implement Default trait for given type using inner type
 macro_rules! derive_default {
     ($type:ident, $inner:ident) => {
-        impl<'a, K: 'a, V: 'a> Default for $type<'a, K, V> {
+        impl<'a, K: 'a, V: 'a, A: 'a> Default for $type<'a, K, V, A> {
             fn default() -> Self {
                 $type($inner::default())
             }
@@ -245,8 +467,8 @@ macro_rules! derive_default_new {
     ($type:ident, $inner:ident) => {
 
         derive_default!($type, $inner);
-        impl<'a, K: 'a, V: 'a> $type<'a, K, V> { // new takes a ref
-            pub fn new(node: &'a Node<K, V>, size: usize) -> $type<'a, K, V> {
+        impl<'a, K: 'a, V: 'a, A: 'a> $type<'a, K, V, A> { // new takes a ref
+            pub fn new(node: &'a Node<K, V, A>, size: usize) -> $type<'a, K, V, A> {
                 $type($inner::new(node, size))
             }
         }
@@ -254,8 +476,8 @@ macro_rules! derive_default_new {
     ($type:ident, $inner:ident, $mut:expr) => {
 
         derive_default!($type, $inner);
-        impl<'a, K: 'a, V: 'a> $type<'a, K, V> { // new takes a mutable ref
-            pub fn new(node: &'a mut Node<K, V>, size: usize) -> $type<'a, K, V> {
+        impl<'a, K: 'a, V: 'a, A: 'a> $type<'a, K, V, A> { // new takes a mutable ref
+            pub fn new(node: &'a mut Node<K, V, A>, size: usize) -> $type<'a, K, V, A> {
                 $type($inner::new(node, size))
             }
         }
@@ -270,23 +492,31 @@ derive_default_new!(ValuesIter, BaseIter);
 derive_default_new!(ValuesIterMut, BaseIterMut, true);
 derive_default_new!(LeafPairsIter, BaseIter);
 derive_default_new!(LeafPairsIterMut, BaseIterMut, true);
+derive_default_new!(KeysIter, BaseIterKeyed);
+derive_default_new!(PairsIter, BaseIterKeyed);
+
+impl<'a, K: 'a, V: 'a, A: 'a> PairsIter<'a, K, V, A> {
+    pub fn with_key(node: &'a Node<K, V, A>, bytes: Vec<u8>, size: usize) -> PairsIter<'a, K, V, A> {
+        PairsIter(BaseIterKeyed::with_key(node, bytes, size))
+    }
+}
 
-impl<K, V> Default for IntoIter<K, V> {
+impl<K, V, A> Default for IntoIter<K, V, A> {
     fn default() -> Self {
         IntoIter(BaseIterOwned::default())
     }
 }
 
-impl<K, V> IntoIter<K, V> {
-    pub fn new(node: Node<K, V>) -> IntoIter<K, V> {
-        IntoIter(BaseIterOwned::new(node))
+impl<K, V, A> IntoIter<K, V, A> {
+    pub fn new(node: Node<K, V, A>, bytes: Vec<u8>) -> IntoIter<K, V, A> {
+        IntoIter(BaseIterOwned::new(node, bytes))
     }
 }
 
 /*-----------------------------------------------------------------------*/
 // Iterator trait impl for custom iterator types which leverage base iterator
 
-impl<'a, K: 'a, V: 'a> Iterator for LabelsIter<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: 'a> Iterator for LabelsIter<'a, K, V, A> {
     type Item = &'a [u8];
     fn next(&mut self) -> Option<&'a [u8]> {
         let result = self.0.next(IterationType::Labels);
@@ -294,7 +524,7 @@ impl<'a, K: 'a, V: 'a> Iterator for LabelsIter<'a, K, V> {
     }
 }
 
-impl<'a, K: 'a, V: 'a> Iterator for ValuesIter<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: 'a> Iterator for ValuesIter<'a, K, V, A> {
     type Item = &'a V;
     fn next(&mut self) -> Option<Self::Item> {
         let result = self.0.next(IterationType::Values);
@@ -302,15 +532,15 @@ impl<'a, K: 'a, V: 'a> Iterator for ValuesIter<'a, K, V> {
     }
 }
 
-impl<'a, K: 'a, V: 'a> Iterator for ValuesIterMut<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: 'a> Iterator for ValuesIterMut<'a, K, V, A> {
     type Item = &'a mut V;
     fn next(&mut self) -> Option<&'a mut V> {
-        let result = self.0.next(IterationType::ValuesMut);
+        let result = self.0.next(IterationType::ValuesMut, false);
         result.and_then(|r| enum_extract!(r, NextType::ValueRefMut))
     }
 }
 
-impl<'a, K: 'a, V: 'a> Iterator for LeafPairsIter<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: 'a> Iterator for LeafPairsIter<'a, K, V, A> {
     type Item = (&'a [u8], &'a V);
     fn next(&mut self) -> Option<(&'a [u8], &'a V)> {
         let result = self.0.next(IterationType::LabelsValues);
@@ -318,18 +548,148 @@ impl<'a, K: 'a, V: 'a> Iterator for LeafPairsIter<'a, K, V> {
     }
 }
 
-impl<'a, K: 'a, V: 'a> Iterator for LeafPairsIterMut<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: 'a> Iterator for LeafPairsIterMut<'a, K, V, A> {
     type Item = (&'a [u8], &'a mut V);
     fn next(&mut self) -> Option<(&'a [u8], &'a mut V)> {
-        let result = self.0.next(IterationType::LabelsValuesMut);
+        let result = self.0.next(IterationType::LabelsValuesMut, false);
+        result.and_then(|r| enum_extract!(r, NextType::LeafPairRefMut))
+    }
+}
+
+impl<K, V, A> Iterator for IntoIter<K, V, A> {
+    type Item = (Vec<u8>, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.0.next(IterationType::PairsOwned, false);
+        result.and_then(|r| enum_extract!(r, NextType::PairOwned))
+    }
+}
+
+impl<'a, K: 'a, V: 'a, A: 'a> Iterator for KeysIter<'a, K, V, A> {
+    type Item = Vec<u8>;
+    fn next(&mut self) -> Option<Vec<u8>> {
+        let result = self.0.next(IterationType::Keys);
+        result.and_then(|r| enum_extract!(r, NextType::Key))
+    }
+}
+
+impl<'a, K: 'a, V: 'a, A: 'a> Iterator for PairsIter<'a, K, V, A> {
+    type Item = (Vec<u8>, &'a V);
+    fn next(&mut self) -> Option<(Vec<u8>, &'a V)> {
+        let result = self.0.next(IterationType::KeysValues);
+        result.and_then(|r| enum_extract!(r, NextType::KeyValue))
+    }
+}
+
+/*-----------------------------------------------------------------------*/
+// DoubleEndedIterator trait impl for custom iterator types which leverage base iterator,
+// front and back iteration stop once they meet as tracked by the remaining size
+// (mut and owned iteration share a single deque instead)
+
+impl<'a, K: 'a, V: 'a, A: 'a> DoubleEndedIterator for ValuesIter<'a, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.0.next_back(IterationType::Values);
+        result.and_then(|r| enum_extract!(r, NextType::ValueRef))
+    }
+}
+
+impl<'a, K: 'a, V: 'a, A: 'a> DoubleEndedIterator for LeafPairsIter<'a, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.0.next_back(IterationType::LabelsValues);
+        result.and_then(|r| enum_extract!(r, NextType::LeafPairRef))
+    }
+}
+
+impl<'a, K: 'a, V: 'a, A: 'a> DoubleEndedIterator for ValuesIterMut<'a, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.0.next(IterationType::ValuesMut, true);
+        result.and_then(|r| enum_extract!(r, NextType::ValueRefMut))
+    }
+}
+
+impl<'a, K: 'a, V: 'a, A: 'a> DoubleEndedIterator for LeafPairsIterMut<'a, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.0.next(IterationType::LabelsValuesMut, true);
         result.and_then(|r| enum_extract!(r, NextType::LeafPairRefMut))
     }
 }
 
-impl<K, V> Iterator for IntoIter<K, V> {
-    type Item = V;
+impl<K, V, A> DoubleEndedIterator for IntoIter<K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.0.next(IterationType::PairsOwned, true);
+        result.and_then(|r| enum_extract!(r, NextType::PairOwned))
+    }
+}
+
+impl<'a, K: 'a, V: 'a, A: 'a> DoubleEndedIterator for KeysIter<'a, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.0.next_back(IterationType::Keys);
+        result.and_then(|r| enum_extract!(r, NextType::Key))
+    }
+}
+
+impl<'a, K: 'a, V: 'a, A: 'a> DoubleEndedIterator for PairsIter<'a, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.0.next_back(IterationType::KeysValues);
+        result.and_then(|r| enum_extract!(r, NextType::KeyValue))
+    }
+}
+
+/*-----------------------------------------------------------------------*/
+// Typed iterator trait impls, wrapping the keyed iterators
+
+impl<'a, K, V, A> From<KeysIter<'a, K, V, A>> for TypedKeysIter<'a, K, V, A> {
+    fn from(iter: KeysIter<'a, K, V, A>) -> Self {
+        TypedKeysIter(iter)
+    }
+}
+
+impl<'a, K, V, A> From<PairsIter<'a, K, V, A>> for TypedPairsIter<'a, K, V, A> {
+    fn from(iter: PairsIter<'a, K, V, A>) -> Self {
+        TypedPairsIter(iter)
+    }
+}
+
+impl<K, V, A> From<IntoIter<K, V, A>> for TypedIntoIter<K, V, A> {
+    fn from(iter: IntoIter<K, V, A>) -> Self {
+        TypedIntoIter(iter)
+    }
+}
+
+impl<'a, K: TrieKey + 'a, V: 'a, A: 'a> Iterator for TypedKeysIter<'a, K, V, A> {
+    type Item = K::Owned;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|k| decode_stored::<K>(&k))
+    }
+}
+
+impl<'a, K: TrieKey + 'a, V: 'a, A: 'a> DoubleEndedIterator for TypedKeysIter<'a, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|k| decode_stored::<K>(&k))
+    }
+}
+
+impl<'a, K: TrieKey + 'a, V: 'a, A: 'a> Iterator for TypedPairsIter<'a, K, V, A> {
+    type Item = (K::Owned, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (decode_stored::<K>(&k), v))
+    }
+}
+
+impl<'a, K: TrieKey + 'a, V: 'a, A: 'a> DoubleEndedIterator for TypedPairsIter<'a, K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, v)| (decode_stored::<K>(&k), v))
+    }
+}
+
+impl<K: TrieKey, V, A> Iterator for TypedIntoIter<K, V, A> {
+    type Item = (K::Owned, V);
     fn next(&mut self) -> Option<Self::Item> {
-        let result = self.0.next(IterationType::ValuesOwned);
-        result.and_then(|r| enum_extract!(r, NextType::ValueOwned))
+        self.0.next().map(|(k, v)| (decode_stored::<K>(&k), v))
+    }
+}
+
+impl<K: TrieKey, V, A> DoubleEndedIterator for TypedIntoIter<K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, v)| (decode_stored::<K>(&k), v))
     }
 }
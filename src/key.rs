@@ -0,0 +1,408 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+// Keys are stored as byte sequences, a TrieKey encodes a key into bytes such that the byte order
+// (lexicographic) matches the key type's own order, hence ordered iteration and range style
+// queries remain meaningful. Decoding recovers an owned key from the stored bytes
+
+// e.g. integers are encoded big-endian with signed integers having their sign bit flipped
+// (so negatives sort first), composite tuples escape each element's bytes and terminate each
+// element, so that a shorter element sorts before a longer element that extends it
+
+pub trait TrieKey {
+    // Key type recovered when decoding e.g. String for str
+    type Owned;
+
+    fn encode(&self) -> Cow<'_, [u8]>;
+
+    // Returns None if bytes weren't produced by encode
+    fn decode(bytes: &[u8]) -> Option<Self::Owned>;
+}
+
+// Element terminator and escape used by composite keys, a zero byte within an element
+// is escaped as 0x00 0xFF and an element ends with 0x00 0x01
+const ESCAPE: u8 = 0x00;
+const ESCAPED_ZERO: u8 = 0xFF;
+const TERMINATOR: u8 = 0x01;
+
+impl<T: TrieKey + ?Sized> TrieKey for &T {
+    type Owned = T::Owned;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        (**self).encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self::Owned> {
+        T::decode(bytes)
+    }
+}
+
+// Macro to implement TrieKey for smart pointers, encoding as the pointee does
+macro_rules! pointer_key {
+    ($($pointer:ident),*) => {
+        $(
+            impl<T: TrieKey + ?Sized> TrieKey for $pointer<T> {
+                type Owned = T::Owned;
+
+                fn encode(&self) -> Cow<'_, [u8]> {
+                    (**self).encode()
+                }
+
+                fn decode(bytes: &[u8]) -> Option<Self::Owned> {
+                    T::decode(bytes)
+                }
+            }
+        )*
+    };
+}
+
+pointer_key!(Box, Rc, Arc);
+
+impl<T: TrieKey + ToOwned + ?Sized> TrieKey for Cow<'_, T> {
+    type Owned = <T as TrieKey>::Owned;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        (**self).encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<<T as TrieKey>::Owned> {
+        T::decode(bytes)
+    }
+}
+
+// Escape hatch for byte-like types without a TrieKey impl of their own,
+// e.g. Bytes(buf) keys the trie by whatever buf.as_ref() holds
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bytes<T>(pub T);
+
+impl<T: AsRef<[u8]>> TrieKey for Bytes<T> {
+    type Owned = Vec<u8>;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.0.as_ref())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Vec<u8>> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl TrieKey for str {
+    type Owned = String;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<String> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+impl TrieKey for String {
+    type Owned = String;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<String> {
+        str::decode(bytes)
+    }
+}
+
+impl TrieKey for [u8] {
+    type Owned = Vec<u8>;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Vec<u8>> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl TrieKey for Vec<u8> {
+    type Owned = Vec<u8>;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Vec<u8>> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl<const N: usize> TrieKey for [u8; N] {
+    type Owned = [u8; N];
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<[u8; N]> {
+        bytes.try_into().ok()
+    }
+}
+
+// Macro to implement TrieKey for unsigned integers, big-endian bytes sort numerically
+macro_rules! unsigned_key {
+    ($($type:ty),*) => {
+        $(
+            impl TrieKey for $type {
+                type Owned = $type;
+
+                fn encode(&self) -> Cow<'_, [u8]> {
+                    Cow::Owned(self.to_be_bytes().to_vec())
+                }
+
+                fn decode(bytes: &[u8]) -> Option<$type> {
+                    Some(<$type>::from_be_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+// Macro to implement TrieKey for signed integers, flipping the sign bit
+// moves negative numbers (in two's complement) below the positive numbers
+macro_rules! signed_key {
+    ($($type:ty),*) => {
+        $(
+            impl TrieKey for $type {
+                type Owned = $type;
+
+                fn encode(&self) -> Cow<'_, [u8]> {
+                    let mut bytes = self.to_be_bytes();
+                    bytes[0] ^= 0x80;
+                    Cow::Owned(bytes.to_vec())
+                }
+
+                fn decode(bytes: &[u8]) -> Option<$type> {
+                    let mut bytes: [u8; std::mem::size_of::<$type>()] = bytes.try_into().ok()?;
+                    bytes[0] ^= 0x80;
+                    Some(<$type>::from_be_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+unsigned_key!(u8, u16, u32, u64, u128, usize);
+signed_key!(i8, i16, i32, i64, i128, isize);
+
+impl TrieKey for bool {
+    type Owned = bool;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(vec![*self as u8])
+    }
+
+    fn decode(bytes: &[u8]) -> Option<bool> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+
+// Chars are encoded as their big-endian code point, so char sequences can use Vec<char>
+impl TrieKey for char {
+    type Owned = char;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        (*self as u32).encode().into_owned().into()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<char> {
+        char::from_u32(u32::decode(bytes)?)
+    }
+}
+
+impl TrieKey for Vec<char> {
+    type Owned = Vec<char>;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.iter().flat_map(|c| (*c as u32).to_be_bytes()).collect())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Vec<char>> {
+        bytes.chunks(4).map(char::decode).collect()
+    }
+}
+
+impl TrieKey for Ipv4Addr {
+    type Owned = Ipv4Addr;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.octets().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Ipv4Addr> {
+        <[u8; 4]>::try_from(bytes).ok().map(Ipv4Addr::from)
+    }
+}
+
+impl TrieKey for Ipv6Addr {
+    type Owned = Ipv6Addr;
+
+    fn encode(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.octets().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Ipv6Addr> {
+        <[u8; 16]>::try_from(bytes).ok().map(Ipv6Addr::from)
+    }
+}
+
+// Decodes a key read back out of a trie, stored keys were all produced by the key type's
+// encode so failing to decode is a bug rather than something to skip over
+pub(crate) fn decode_stored<K: TrieKey + ?Sized>(bytes: &[u8]) -> K::Owned {
+    K::decode(bytes).expect("stored key doesn't decode as the trie's key type")
+}
+
+// Appends an escaped and terminated composite key element
+pub(crate) fn encode_element(bytes: &mut Vec<u8>, element: &[u8]) {
+    for b in element {
+        bytes.push(*b);
+
+        if *b == ESCAPE {
+            bytes.push(ESCAPED_ZERO);
+        }
+    }
+
+    bytes.push(ESCAPE);
+    bytes.push(TERMINATOR);
+}
+
+// Splits off the first composite key element, unescaping it
+pub(crate) fn decode_element(bytes: &[u8]) -> Option<(Vec<u8>, &[u8])> {
+    let mut element = Vec::new();
+    let mut i = 0;
+
+    loop {
+        match (bytes.get(i)?, bytes.get(i + 1)) {
+            (&ESCAPE, Some(&TERMINATOR)) => return Some((element, &bytes[i + 2..])),
+            (&ESCAPE, Some(&ESCAPED_ZERO)) => {
+                element.push(ESCAPE);
+                i += 2;
+            },
+            (&ESCAPE, _) => return None,
+            (b, _) => {
+                element.push(*b);
+                i += 1;
+            },
+        }
+    }
+}
+
+// Macro to implement TrieKey for tuples as composite keys
+macro_rules! tuple_key {
+    ($($name:ident : $index:tt),*) => {
+        impl<$($name: TrieKey),*> TrieKey for ($($name,)*) {
+            type Owned = ($($name::Owned,)*);
+
+            fn encode(&self) -> Cow<'_, [u8]> {
+                let mut bytes = Vec::new();
+                $(encode_element(&mut bytes, &self.$index.encode());)*
+                Cow::Owned(bytes)
+            }
+
+            fn decode(bytes: &[u8]) -> Option<Self::Owned> {
+                let rest = bytes;
+                $(
+                    let (element, rest) = decode_element(rest)?;
+                    #[allow(non_snake_case)]
+                    let $name = $name::decode(&element)?;
+                )*
+
+                if !rest.is_empty() {
+                    return None
+                }
+
+                Some(($($name,)*))
+            }
+        }
+    };
+}
+
+tuple_key!(T0: 0, T1: 1);
+tuple_key!(T0: 0, T1: 1, T2: 2);
+tuple_key!(T0: 0, T1: 1, T2: 2, T3: 3);
+
+// TrieKey unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks encoded byte order agrees with the keys' own order, and that keys round trip
+    fn check_order<K>(mut keys: Vec<K>)
+    where K: TrieKey<Owned = K> + Ord + Clone + std::fmt::Debug
+    {
+        keys.sort();
+
+        for pair in keys.windows(2) {
+            assert!(pair[0].encode() < pair[1].encode(), "{:?}", pair);
+        }
+
+        for k in keys {
+            assert_eq!(Some(k.clone()), K::decode(&k.encode()));
+        }
+    }
+
+    #[test]
+    fn check_integer_order() {
+        check_order(vec![0u64, 1, 255, 256, u64::MAX, 70_000]);
+        check_order(vec![-1i32, 0, 1, i32::MIN, i32::MAX, -256, 300]);
+        check_order(vec![-128i8, -1, 0, 127]);
+        assert_eq!(None, u32::decode(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn check_composite_order() {
+        check_order(vec![(1u32, "b".to_string()), (1, "a".to_string()), (0, "zz".to_string()), (1, "".to_string())]);
+        check_order(vec![("ab".to_string(), 2i16), ("a".to_string(), 9), ("a\0".to_string(), -4), ("ab\0b".to_string(), 0)]);
+        check_order(vec![('a', 'b', 1u8), ('a', 'a', 2), ('é', 'a', 0)]);
+        assert_eq!(None, <(u8, u8)>::decode(&[1, 0, 1]));
+    }
+
+    #[test]
+    fn check_misc_order() {
+        check_order(vec![Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(9, 255, 255, 255), Ipv4Addr::new(192, 168, 1, 1)]);
+        check_order(vec![Ipv6Addr::LOCALHOST, Ipv6Addr::UNSPECIFIED]);
+        check_order(vec!['z', 'a', 'é', '\u{1F600}']);
+        check_order(vec![vec!['a', 'b'], vec!['a'], vec!['b']]);
+        check_order(vec![true, false]);
+        assert_eq!(Some("anthem".to_string()), <&str>::decode(&"anthem".encode()));
+    }
+
+    #[test]
+    fn check_pointer_keys() {
+        use crate::trie::Trie;
+
+        let mut trie: Trie<Box<str>, i32> = Trie::new();
+        trie.insert("anthem".into(), 1);
+        assert_eq!(Some(&1), trie.search("anthem".into()));
+        assert_eq!(Some(("anthem".to_string(), &1)), trie.first_key_value());
+
+        let mut trie: Trie<Cow<str>, i32> = Trie::new();
+        trie.insert(Cow::Owned("anti".to_string()), 2);
+        assert_eq!(Some(&2), trie.search(Cow::Borrowed("anti")));
+
+        let mut trie: Trie<Rc<[u8]>, i32> = Trie::new();
+        trie.insert(Rc::from(&b"and"[..]), 3);
+        assert_eq!(Some(&3), trie.search(Rc::from(&b"and"[..])));
+
+        // Byte-like types without an impl go through Bytes
+        let mut trie: Trie<Bytes<[u8; 3]>, i32> = Trie::new();
+        trie.insert(Bytes(*b"ant"), 4);
+        assert_eq!(Some((b"ant".to_vec(), &4)), trie.first_key_value());
+        assert_eq!(Some(vec![1, 2]), Arc::<[u8]>::decode(&[1, 2]));
+    }
+}
@@ -1,7 +1,22 @@
 pub mod trie;
+pub mod set;
+pub mod aggregate;
+pub mod key;
+pub mod bits;
+pub mod routing;
+pub mod str_trie;
+pub mod normalize;
+pub mod path_trie;
+pub mod router;
+pub mod domain_trie;
 mod node;
 mod traverse;
 mod query;
 mod iter;
 mod delete;
+mod subtrie;
+mod cursor;
+mod matcher;
+mod tokenize;
+mod segment;
 mod macros;
@@ -6,7 +6,7 @@ fn main() {
     println!("Search suggestions");
 
     // Search terms is a trie,
-    // K is AsRef<[u8]> which &str is and u16 fits into any generic V
+    // K is TrieKey which &str is and u16 fits into any generic V
     let search_terms: Trie<&str, u16> =
         [("mobile", 10),("mandala", 67),("mousy brown hair dye", 23),("moneypot", 45),
          ("mexican sombrero", 27), ("muscle cars", 11), ("mouthguard", 8),
@@ -58,7 +58,7 @@ fn main() {
 }
 
 
-pub fn flatten_keys<'a>(keys: Option<&'a Vec<Vec<u8>>>) -> Vec<&'a str> {
+pub fn flatten_keys(keys: Option<&Vec<Vec<u8>>>) -> Vec<&str> {
     keys.map_or(vec![], |k| {
         let mut v = k.iter()
             .map(|bytes| std::str::from_utf8(bytes).unwrap_or_default())
@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::node::Node;
+
+// Aho-Corasick automaton for finding every stored key within a text in a single pass.
+// The trie's labels are expanded into one state per byte, and each state gains a failure
+// link to the state of its longest proper suffix that is also a path in the trie, along
+// with a dictionary link to the nearest such suffix state which ends a key. The empty key
+// is never reported as it would match at every position
+
+#[derive(Clone, Debug)]
+pub struct Matcher<'a, V> {
+    states: Vec<State<'a, V>>,
+    kind: MatchKind,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MatchKind {
+    // Every occurrence of every key, including overlapping ones, ordered by end position
+    #[default]
+    Overlapping,
+    // Non overlapping, the leftmost starting match is reported preferring the longest key
+    LeftmostLongest,
+    // Non overlapping, the leftmost starting match is reported preferring keys in key order.
+    // Keys matching at the same position are prefixes of one another, so the shortest wins
+    LeftmostFirst,
+}
+
+#[derive(Clone, Debug)]
+struct State<'a, V> {
+    goto: BTreeMap<u8, usize>,
+    fail: usize,
+    dict: Option<usize>, // nearest state along failure links which ends a key
+    depth: usize,
+    value: Option<&'a V>,
+}
+
+const ROOT: usize = 0;
+
+impl<'a, V> State<'a, V> {
+    fn new(depth: usize) -> Self {
+        State {
+            goto: BTreeMap::new(),
+            fail: ROOT,
+            dict: None,
+            depth,
+            value: None,
+        }
+    }
+}
+
+impl<'a, V> Matcher<'a, V> {
+    pub(crate) fn new<K, A>(root: Option<&'a Node<K, V, A>>) -> Self {
+        let mut states = vec![State::new(0)];
+        let mut stack = root.into_iter().map(|n| (n, ROOT)).collect::<Vec<_>>();
+
+        // Expand each edge's label into a chain of states
+        while let Some((node, state)) = stack.pop() {
+            let mut child = node.first_edge();
+
+            while let Some(next) = child {
+                let label = next.label().unwrap();
+                let mut current = state;
+
+                for b in label {
+                    states.push(State::new(states[current].depth + 1));
+                    let id = states.len() - 1;
+                    states[current].goto.insert(*b, id);
+                    current = id;
+                }
+
+                states[current].value = next.value();
+                stack.push((next, current));
+                child = node.next_edge(label[0]);
+            }
+        }
+
+        // Breadth first, so a state's failure target is resolved before the state itself
+        let mut queue = states[ROOT].goto.values().copied().collect::<VecDeque<_>>();
+
+        while let Some(state) = queue.pop_front() {
+            let edges = states[state].goto.iter().map(|(b, s)| (*b, *s)).collect::<Vec<_>>();
+
+            for (b, child) in edges {
+                let mut fail = states[state].fail;
+
+                while fail != ROOT && !states[fail].goto.contains_key(&b) {
+                    fail = states[fail].fail;
+                }
+
+                let fail = states[fail].goto.get(&b).copied().unwrap_or(ROOT);
+
+                states[child].fail = fail;
+                states[child].dict = if states[fail].value.is_some() { Some(fail) } else { states[fail].dict };
+                queue.push_back(child);
+            }
+        }
+
+        Matcher {
+            states,
+            kind: MatchKind::default(),
+        }
+    }
+
+    pub fn with_match_kind(mut self, kind: MatchKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn match_kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    // Iterates matches within text as (start, end, value), text[start..end] being the key
+    pub fn find_iter<'m, 't, T>(&'m self, text: &'t T) -> FindIter<'m, 't, 'a, V>
+    where T: AsRef<[u8]> + ?Sized
+    {
+        FindIter {
+            matcher: self,
+            text: text.as_ref(),
+            pos: 0,
+            state: ROOT,
+            output: None,
+        }
+    }
+
+    // Whether any key occurs within text
+    pub fn is_match<T>(&self, text: &T) -> bool
+    where T: AsRef<[u8]> + ?Sized
+    {
+        self.find_iter(text).next().is_some()
+    }
+
+    fn next_state(&self, mut state: usize, b: u8) -> usize {
+        loop {
+            if let Some(next) = self.states[state].goto.get(&b) {
+                return *next
+            }
+
+            if state == ROOT {
+                return ROOT
+            }
+
+            state = self.states[state].fail;
+        }
+    }
+
+    // First state ending a key among state and its dictionary links
+    fn output(&self, state: usize) -> Option<usize> {
+        if self.states[state].value.is_some() { Some(state) } else { self.states[state].dict }
+    }
+}
+
+pub struct FindIter<'m, 't, 'a, V> {
+    matcher: &'m Matcher<'a, V>,
+    text: &'t [u8],
+    pos: usize,
+    state: usize,
+    output: Option<usize>, // pending overlapping output at pos
+}
+
+impl<'a, V> FindIter<'_, '_, 'a, V> {
+    fn next_overlapping(&mut self) -> Option<(usize, usize, &'a V)> {
+        let states = &self.matcher.states;
+
+        loop {
+            if let Some(out) = self.output {
+                self.output = states[out].dict;
+                return Some((self.pos - states[out].depth, self.pos, states[out].value?))
+            }
+
+            let b = *self.text.get(self.pos)?;
+            self.state = self.matcher.next_state(self.state, b);
+            self.pos += 1;
+            self.output = self.matcher.output(self.state);
+        }
+    }
+
+    // Scans from pos for the leftmost match, stopping once no later match can start at or
+    // before the best start found. Scanning resumes from the root after the match's end
+    fn next_leftmost(&mut self, longest: bool) -> Option<(usize, usize, &'a V)> {
+        let states = &self.matcher.states;
+        let mut state = ROOT;
+        let mut best: Option<(usize, usize, &'a V)> = None;
+
+        for (i, b) in self.text.iter().enumerate().skip(self.pos) {
+            state = self.matcher.next_state(state, *b);
+            let end = i + 1;
+
+            // Matches yet to come start no earlier than the current state's start
+            if let Some((start, _, _)) = best {
+                let earliest = end - states[state].depth;
+
+                if earliest > start || (!longest && earliest == start) {
+                    break
+                }
+            }
+
+            let mut out = self.matcher.output(state);
+
+            while let Some(s) = out {
+                let start = end - states[s].depth;
+
+                let better = match best {
+                    None => true,
+                    Some((best_start, _, _)) => start < best_start || (longest && start == best_start),
+                };
+
+                if better {
+                    best = states[s].value.map(|v| (start, end, v));
+                }
+
+                out = states[s].dict;
+            }
+        }
+
+        let (start, end, v) = best?;
+        self.pos = end;
+        Some((start, end, v))
+    }
+}
+
+impl<'a, V> Iterator for FindIter<'_, '_, 'a, V> {
+    type Item = (usize, usize, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.matcher.kind {
+            MatchKind::Overlapping => self.next_overlapping(),
+            MatchKind::LeftmostLongest => self.next_leftmost(true),
+            MatchKind::LeftmostFirst => self.next_leftmost(false),
+        }
+    }
+}
+
+// Matcher unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::Trie;
+
+    fn matches_helper(trie: &Trie<&str, i32>, kind: MatchKind, text: &str) -> Vec<(usize, usize, i32)> {
+        trie.build_matcher().with_match_kind(kind).find_iter(text).map(|(s, e, v)| (s, e, *v)).collect()
+    }
+
+    #[test]
+    fn check_overlapping_matches() {
+        let trie: Trie<_, _> = [("he", 1), ("she", 2), ("his", 3), ("hers", 4)].iter().cloned().collect();
+
+        assert_eq!(vec![(1, 4, 2), (2, 4, 1), (2, 6, 4)], matches_helper(&trie, MatchKind::Overlapping, "ushers"));
+        assert_eq!(vec![(0, 3, 3), (3, 6, 2), (4, 6, 1)], matches_helper(&trie, MatchKind::Overlapping, "hisshe"));
+        assert!(trie.build_matcher().is_match("ahead"));
+        assert!(!trie.build_matcher().is_match("hi"));
+    }
+
+    #[test]
+    fn check_leftmost_matches() {
+        let trie: Trie<_, _> = [("abcd", 1), ("bc", 2), ("b", 3), ("cde", 4), ("ab", 5), ("e", 6)].iter().cloned().collect();
+
+        assert_eq!(vec![(0, 4, 1), (4, 5, 6)], matches_helper(&trie, MatchKind::LeftmostLongest, "abcde"));
+        assert_eq!(vec![(0, 2, 5), (2, 5, 4)], matches_helper(&trie, MatchKind::LeftmostFirst, "abcde"));
+        assert_eq!(vec![(1, 3, 2), (4, 5, 6)], matches_helper(&trie, MatchKind::LeftmostLongest, "xbcxe"));
+        assert_eq!(vec![(1, 2, 3), (4, 5, 6)], matches_helper(&trie, MatchKind::LeftmostFirst, "xbcxe"));
+        assert_eq!(Vec::<(usize, usize, i32)>::new(), matches_helper(&trie, MatchKind::LeftmostFirst, "xyz"));
+    }
+}
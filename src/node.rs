@@ -1,67 +1,80 @@
 pub mod view;
+pub mod algebra;
 
 use std::mem;
+use std::ops::Bound::{Excluded, Unbounded};
 use std::ops::Deref;
 use std::fmt;
 use std::borrow::Cow;
 use std::marker::PhantomData;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::delete::{Playback, Cursor, capture};
-use crate::iter::{LabelsIter, ValuesIter, ValuesIterMut, IntoIter, LeafPairsIter, LeafPairsIterMut};
+use crate::iter::{LabelsIter, ValuesIter, ValuesIterMut, IntoIter, LeafPairsIter, LeafPairsIterMut, KeysIter, PairsIter};
 use crate::traverse::{TraverseType, TraverseResult, KeyMatch, SuffixType, traverse_match, traverse};
 use crate::node::view::{NodeView, NodeViewMut, NodeViewOwned};
+use crate::aggregate::TrieAggregate;
 
-// A key is not actually stored in the Trie but instead a Vec<u8>
-// The trie is accessed via anything the implements the trait AsRef<[u8]>
-// To link the traits and generics involved, K is in fact a zero-sized PhantomData type
+// The trie is accessed via anything that implements the trait TrieKey, which encodes keys into bytes
+// To link the traits and generics involved, K is in fact a zero-sized PhantomData type (used to decode keys)
 // To prevent the unused K from affecting the drop check anaylsis it is wrapped in an fn() (just like Empty Iterator)
+// Edges are kept ordered by their edge key (the first byte of the child's label), allowing ordered traversal
+// Each node also tracks the number of keys in its subtree (itself included), used for order statistics,
+// along with the aggregate summary of its subtree's values (None until computed, or for trivial aggregates)
 
 #[derive(Clone, PartialEq, Eq)]
-pub struct Node<K, V> {
+pub struct Node<K, V, A = ()> {
     label: Option<Vec<u8>>,
     value: Option<Box<V>>,
     tag: NodeType,
-    edges: HashMap<u8, Box<Node<K, V>>>,
+    edges: BTreeMap<u8, Box<Node<K, V, A>>>,
+    count: usize,
+    summary: Option<A>,
     key: PhantomData<fn() -> K>,  // from Empty Iterator
 }
 
-impl<K, V> Default for Node<K, V> {
+impl<K, V, A> Default for Node<K, V, A> {
     fn default() -> Self {
         Node {
             label: None,
             value: None,
             tag: NodeType::default(),
-            edges: HashMap::new(),
+            edges: BTreeMap::new(),
+            count: 0,
+            summary: None,
             key: PhantomData,
         }
     }
 }
 
-impl<K, V> fmt::Debug for Node<K, V> {
+impl<K, V, A> fmt::Debug for Node<K, V, A> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("Node")
             .field("label", &self.label.as_deref())
             .field("value", &format_args!(".."))
             //.field("value", &self.value.as_deref())
             .field("tag", &self.tag)
+            .field("count", &self.count)
             .field("edges", &self.edges)
             //.field("key", &format_args!("_"))
             .finish()
     }
 }
 
+// A detached subtree along with its full key bytes
+pub(crate) type Subtree<K, V, A> = (Vec<u8>, Box<Node<K, V, A>>);
+
+// A node ref along with its key bytes
+pub(crate) type KeyedNode<'a, K, V, A> = (&'a Node<K, V, A>, Vec<u8>);
+
 // A key node contains a value and inner node does not
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
 pub enum NodeType {
     Key,
+    #[default]
     Inner,
 }
 
-impl Default for NodeType {
-    fn default() -> Self { NodeType::Inner }
-}
-
 // Define type which reflects outgoing edges number
 #[derive(Debug, PartialEq)]
 pub enum EdgeType {
@@ -69,13 +82,15 @@ pub enum EdgeType {
     Branching(usize),  // 2 or more
 }
 
-impl<K, V> Node<K, V> {
+impl<K, V, A> Node<K, V, A> {
     pub fn new(label: Option<Vec<u8>>, tag: NodeType, value: Option<Box<V>>) -> Self {
         Node {
             label,
             value,
             tag,
-            edges: HashMap::new(),
+            edges: BTreeMap::new(),
+            count: (tag == NodeType::Key) as usize,
+            summary: None,
             key: PhantomData,
         }
     }
@@ -87,6 +102,17 @@ impl<K, V> Node<K, V> {
         self.label.as_deref()
     }
 
+    // Returns ref to value associated with node, if node is a key node
+    #[inline]
+    pub(crate) fn value(&self) -> Option<&V> {
+        self.value.as_deref()
+    }
+
+    #[inline]
+    pub(crate) fn value_mut(&mut self) -> Option<&mut V> {
+        self.value.as_deref_mut()
+    }
+
     #[inline]
     pub fn is_key(&self) -> bool {
         self.tag == NodeType::Key
@@ -103,20 +129,61 @@ impl<K, V> Node<K, V> {
 
     #[allow(clippy::borrowed_box)]
     #[inline]
-    pub(crate) fn lookup_edge(&self, first: u8) -> Option<&Box<Node<K, V>>> {
+    pub(crate) fn lookup_edge(&self, first: u8) -> Option<&Box<Node<K, V, A>>> {
         self.edges.get(&first)
     }
 
     #[allow(clippy::borrowed_box)]
     #[inline]
-    pub(crate) fn lookup_edge_mut(&mut self, first: u8) -> Option<&mut Box<Node<K, V>>> {
+    pub(crate) fn lookup_edge_mut(&mut self, first: u8) -> Option<&mut Box<Node<K, V, A>>> {
         self.edges.get_mut(&first)
     }
 
+    // Detaches the child at edge key, letting a mutable cursor hold the nodes along its path
+    // by value. Counts and summaries are left as is, as the child is attached again
+    pub(crate) fn detach_edge(&mut self, first: u8) -> Option<Box<Node<K, V, A>>> {
+        self.edges.remove(&first)
+    }
+
+    pub(crate) fn attach_edge(&mut self, child: Box<Node<K, V, A>>) {
+        let first = child.label().unwrap()[0];
+        self.edges.insert(first, child);
+    }
+
+    // Ordered edge traversal helpers, used to step between sibling nodes
+
+    #[inline]
+    pub(crate) fn first_edge(&self) -> Option<&Node<K, V, A>> {
+        self.edges.values().next().map(|b| &**b)
+    }
+
+    #[inline]
+    pub(crate) fn last_edge(&self) -> Option<&Node<K, V, A>> {
+        self.edges.values().next_back().map(|b| &**b)
+    }
+
+    // Returns the sibling edge following edge key
+    #[inline]
+    pub(crate) fn next_edge(&self, key: u8) -> Option<&Node<K, V, A>> {
+        self.edges.range((Excluded(key), Unbounded)).next().map(|(_, b)| &**b)
+    }
+
+    // Returns the sibling edge preceding edge key
+    #[inline]
+    pub(crate) fn prev_edge(&self, key: u8) -> Option<&Node<K, V, A>> {
+        self.edges.range(..key).next_back().map(|(_, b)| &**b)
+    }
+
+    // Sums the key counts of the edges preceding edge key
+    #[inline]
+    pub(crate) fn count_before(&self, key: u8) -> usize {
+        self.edges.range(..key).map(|(_, b)| b.count).sum()
+    }
+
     // Retrieves value associated with prefix token
     pub fn search(&self, prefix: &[u8]) -> Option<&'_ V> {
-        let current: &Node<K, V> = self;
-        let result: TraverseResult<K, V> = traverse(current, prefix, TraverseType::Search)?;
+        let current: &Node<K, V, A> = self;
+        let result: TraverseResult<K, V, A> = traverse(current, prefix, TraverseType::Search)?;
 
         match result {
             TraverseResult::Terminal(true, n) => n.value.as_deref(),
@@ -124,9 +191,96 @@ impl<K, V> Node<K, V> {
         }
     }
 
+    // Finds the longest key that is a prefix of token, returning the key's length along with
+    // a mut ref to its value. Node's value and edges are borrowed separately so that the
+    // best value found so far can be held onto while descending further
+    pub(crate) fn longest_prefix_match_mut(&mut self, token: &[u8]) -> Option<(usize, &'_ mut V)> {
+        let mut current: &mut Node<K, V, A> = self;
+        let mut nav_token: &[u8] = token;
+        let mut len = 0;
+        let mut result = None;
+
+        loop {
+            let Node { value, edges, tag, .. } = current;
+
+            if *tag == NodeType::Key {
+                result = value.as_deref_mut().map(|v| (len, v));
+            }
+
+            let Some(next) = nav_token.first().and_then(|b| edges.get_mut(b)) else {
+                break
+            };
+
+            let label = next.label.as_deref().unwrap();
+
+            if !nav_token.starts_with(label) {
+                break
+            }
+
+            len += label.len();
+            nav_token = &nav_token[label.len()..];
+            current = &mut **next;
+        }
+
+        result
+    }
+
+    // Retrieves mut value associated with prefix token
+    pub(crate) fn search_mut(&mut self, prefix: &[u8]) -> Option<&'_ mut V> {
+        let mut current: &mut Node<K, V, A> = self;
+        let mut nav_token: &[u8] = prefix;
+
+        while !nav_token.is_empty() {
+            let next = current.lookup_edge_mut(nav_token[0])?;
+            let label = next.label.as_deref().unwrap();
+
+            if !nav_token.starts_with(label) {
+                return None
+            }
+
+            nav_token = &nav_token[label.len()..];
+            current = &mut **next;
+        }
+
+        current.value.as_deref_mut()
+    }
+
+    // Retrieves mut value associated with token, first inserting default's value if token isn't
+    // a key yet, and returns whether it was inserted. Descends just once, splitting labels on
+    // the way down and bumping key counts on the way back up. Summaries along the path are left
+    // for the trie to refresh, as the value is handed out mutably anyway
+    pub(crate) fn get_or_insert_with<F>(&mut self, token: &[u8], default: F) -> (&'_ mut V, bool)
+    where A: TrieAggregate<V>, F: FnOnce() -> V
+    {
+        let Some(&first) = token.first() else {
+            let inserted = self.tag == NodeType::Inner;
+
+            if inserted {
+                self.tag = NodeType::Key;
+                self.value = Some(Box::new(default()));
+                self.count += 1;
+            }
+
+            return (self.value.as_deref_mut().unwrap(), inserted)
+        };
+
+        let child = self.edges.entry(first)
+            .or_insert_with(|| Box::new(Node::new(Some(token.to_vec()), NodeType::Inner, None)));
+
+        let label = child.label.as_deref().unwrap();
+        let common = label.iter().zip(token).take_while(|(a, b)| a == b).count();
+        child.split_label(common);
+
+        let (value, inserted) = child.get_or_insert_with(&token[common..], default);
+        self.count += inserted as usize;
+        (value, inserted)
+    }
+
     // Helper function to insert bridge node which provides a fork to contain an existing node
     // And create space for a new key fragment
-    fn insert_bridge(&mut self, byte_key: u8, common: Cow<[u8]>, suffix_edge: Cow<[u8]>) -> &mut Box<Node<K, V>> {
+    fn insert_bridge(&mut self, byte_key: u8, common: Cow<[u8]>, suffix_edge: Cow<[u8]>) -> &mut Box<Node<K, V, A>>
+    where A: TrieAggregate<V>
+    {
         if common.is_empty() || suffix_edge.is_empty() {
             unreachable!();
         }
@@ -138,13 +292,120 @@ impl<K, V> Node<K, V> {
         // replace previous key with the edge suffix value (as the common prefix goes in the bridge node)
         old_node.label.replace(suffix_edge.into_owned());
         bridge_node.edges.insert(next_byte_key, old_node);
+        bridge_node.refresh();
 
         self.edges.insert(byte_key, bridge_node);
         self.edges.get_mut(&byte_key).unwrap()
     }
 
+    // Helper function to split node's label at the given index, pushing the label's tail
+    // along with the node's value and edges down into a new child node.
+    // The node itself is left as an inner node holding just the label's head
+    pub(crate) fn split_label(&mut self, at: usize)
+    where A: TrieAggregate<V>
+    {
+        let label = self.label.as_mut().unwrap();
+
+        if at == 0 || at >= label.len() {
+            return
+        }
+
+        let suffix = label.split_off(at);
+        let next_byte_key = suffix[0];
+
+        let mut child = Box::new(Node::new(Some(suffix), self.tag, self.value.take()));
+        child.edges = mem::take(&mut self.edges);
+        child.refresh();
+
+        self.tag = NodeType::Inner;
+        self.edges.insert(next_byte_key, child);
+    }
+
+    // Restores a subtree's compressed form after structural edits, an inner node with no
+    // edges is dropped altogether and an inner passthrough node is merged with its only child
+    pub(crate) fn compress(mut self: Box<Self>) -> Option<Box<Self>> {
+        if self.is_key() {
+            return Some(self)
+        }
+
+        match self.edge_type() {
+            None => None,
+            Some(EdgeType::Single) => {
+                let (_, mut merged) = self.edges.pop_first().unwrap();
+                let mut la = self.label.take().unwrap();
+                la.append(merged.label.as_mut().unwrap());
+                merged.label.replace(la);
+                Some(merged)
+            },
+            _ => Some(self),
+        }
+    }
+
+    // Returns the number of key nodes found in this node's subtree (including itself)
+    #[inline]
+    pub(crate) fn key_count(&self) -> usize {
+        self.count
+    }
+
+    // Returns the node's aggregate summary, None if it hasn't been computed
     #[inline]
-    fn next_helper(&mut self, key: u8) -> Option<& '_ mut Node<K, V>> {
+    pub(crate) fn summary(&self) -> Option<&A> {
+        self.summary.as_ref()
+    }
+
+    // Recomputes the node's key count and aggregate summary from its value and edges,
+    // for use after structural edits where the edges' counts and summaries are accurate
+    pub(crate) fn refresh(&mut self)
+    where A: TrieAggregate<V>
+    {
+        self.count = self.is_key() as usize + self.edges.values().map(|b| b.count).sum::<usize>();
+
+        if A::TRIVIAL {
+            return
+        }
+
+        let mut summary = self.value.as_deref().map_or_else(A::empty, A::lift);
+
+        for child in self.edges.values() {
+            if let Some(s) = child.summary.as_ref() {
+                summary = summary.combine(s);
+            }
+        }
+
+        self.summary = Some(summary);
+    }
+
+    // Refreshes the nodes along token's path bottom up, as only these nodes are affected by an
+    // insert or remove of token. Stops descending where the path no longer matches a label
+    // e.g. a merged label which runs past token, as that subtree is unaffected
+    pub(crate) fn refresh_path(&mut self, token: &[u8])
+    where A: TrieAggregate<V>
+    {
+        if let Some(child) = token.first().and_then(|b| self.edges.get_mut(b)) {
+            let label = child.label.as_deref().unwrap();
+
+            if token.starts_with(label) {
+                let len = label.len();
+                child.refresh_path(&token[len..]);
+            }
+        }
+
+        self.refresh()
+    }
+
+    // Refreshes every node in the subtree bottom up
+    pub(crate) fn refresh_all(&mut self)
+    where A: TrieAggregate<V>
+    {
+        for child in self.edges.values_mut() {
+            child.refresh_all();
+        }
+
+        self.refresh()
+    }
+
+    #[inline]
+    fn next_helper(&mut self, key: u8) -> Option<& '_ mut Node<K, V, A>> {
         self.lookup_edge_mut(key).map(|box_ref| &mut **box_ref)
     }
 
@@ -152,18 +413,19 @@ impl<K, V> Node<K, V> {
     // If value not already present, insert it creating new intermediate
     // nodes as necessary
 
-    pub fn insert(&mut self, token: Cow<[u8]>, value: V) -> Option<V> {
-        let mut current: &mut Node<K, V> = self;
-        let mut temp_box: &mut Box<Node<K, V>>;
+    #[allow(clippy::type_complexity)]
+    pub fn insert(&mut self, token: Cow<[u8]>, value: V) -> Option<V>
+    where A: TrieAggregate<V>
+    {
+        let mut current: &mut Node<K, V, A> = self;
+        let mut temp_box: &mut Box<Node<K, V, A>>;
         let mut nav_token: &[u8] = token.deref();
 
         let (mut input_label, mut interior_label1, mut interior_label2): (Cow<[u8]>, Cow<[u8]>, Cow<[u8]>);
 
-        if token.is_empty() {
-            return None
-        }
+        // The empty key is stored on the start node itself e.g. the root
+        while !nav_token.is_empty() {
 
-        loop {
             // To insert a new node, token slices are matched until we find a hole (None) so to speak,
             // A different cow is created on each iteration, to signal our intent to delay memory allocation
             // until absolutely necessary. Granted this is not a normal COW use case as we don't benefit from Deref
@@ -204,7 +466,7 @@ impl<K, V> Node<K, V> {
                     let key = input_label[0];
                     let label = Some(input_label.into_owned());
 
-                    current.edges.insert(key, Box::new(Node::new(label, NodeType::Key, None)));
+                    current.edges.insert(key, Box::new(Node::new(label, NodeType::Inner, None)));
                     current = &mut **current.edges.get_mut(&key).unwrap();
                     break
 
@@ -217,7 +479,7 @@ impl<K, V> Node<K, V> {
 
         let boxed_value = Box::new(value);
 
-        match current.tag {
+        let result = match current.tag {
             NodeType::Inner => {
                 current.tag = NodeType::Key;
                 current.value.replace(boxed_value);
@@ -229,20 +491,34 @@ impl<K, V> Node<K, V> {
                 let _old = mem::replace(&mut current.edges, old_node.edges);
                 old_node.value.take().map(|bx| *bx) // return Some without Box wrapper around V
             }
-        }
+        };
+
+        // Key counts and summaries along token's path reflect the new value
+        self.refresh_path(&token);
+        result
     }
 
     // Removes node from tree either by unmarking node as a key node, pruning trie or compressing nodes
     // or a combination of both.  Relies on a generated delete plan for guidance when making
     // modifications to trie
-    pub fn remove(&mut self, prefix: &[u8]) -> Option<V> {
-        let mut current: &mut Node<K, V> = self;
+    pub fn remove(&mut self, prefix: &[u8]) -> Option<V>
+    where A: TrieAggregate<V>
+    {
+        let mut current: &mut Node<K, V, A> = self;
         let mut item: Playback;
         let mut counter: u32 = 0;
-        let mut temp: &mut Box<Node<K, V>>;
-        let mut temp_box: Box<Node<K, V>>;
+        let mut temp: &mut Box<Node<K, V, A>>;
+        let mut temp_box: Box<Node<K, V, A>>;
         let mut value: Option<V> = None;
 
+        // The empty key lives on the start node itself, which is never pruned or merged
+        if prefix.is_empty() {
+            let value = current.value.take().map(|bx| *bx);
+            current.tag = NodeType::Inner;
+            current.refresh();
+            return value
+        }
+
         let mut replay = capture(current, prefix)?;
 
         // As long as replay plan isn't empty follow the plan
@@ -278,12 +554,93 @@ impl<K, V> Node<K, V> {
             counter += 1;
         }
 
+        // Nodes kept along prefix's path are refreshed, nodes merged away or pruned don't
+        // need to be (merged nodes keep their subtree intact)
+        self.refresh_path(prefix);
         value
     }
 
+    // Detaches the subtree holding all keys which start with prefix, fixing up the
+    // detached subtree's parent as necessary. Returns the subtree's full key bytes
+    // (which includes its label) along with the subtree
+    pub(crate) fn detach_prefix(&mut self, prefix: &[u8]) -> Option<Subtree<K, V, A>>
+    where A: TrieAggregate<V>
+    {
+        let mut path: Vec<u8> = Vec::new();
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut current: &Node<K, V, A> = self;
+        let mut nav_token: &[u8] = prefix;
+
+        if prefix.is_empty() {
+            return None
+        }
+
+        // Record the edge keys along the path to the subtree, if the prefix ends
+        // in the middle of a label the subtree is the node with that label
+        loop {
+            match traverse_match(current, nav_token)? {
+                KeyMatch {next, common: _, leftover: SuffixType::OnlyToken(sufxt), edge_key} => {
+                    path.push(edge_key);
+                    bytes.extend_from_slice(next.label().unwrap());
+                    current = next;
+                    nav_token = sufxt;
+                },
+                KeyMatch {next, common: _, leftover: SuffixType::Empty | SuffixType::OnlyEdge(_), edge_key} => {
+                    path.push(edge_key);
+                    bytes.extend_from_slice(next.label().unwrap());
+                    break
+                },
+                KeyMatch {leftover: SuffixType::BothEdgeToken(_, _), ..} => return None,
+            }
+        }
+
+        let (edge_key, ancestors) = path.split_last().unwrap();
+
+
+        // Root is the parent, nothing to fix up as root is never a passthrough node
+        let Some((parent_key, rest)) = ancestors.split_last() else {
+            let subtree = self.edges.remove(edge_key).unwrap();
+            self.refresh();
+            return Some((bytes, subtree))
+        };
+
+        let mut grandparent: &mut Node<K, V, A> = self;
+
+        for key in rest {
+            grandparent = grandparent.next_helper(*key).unwrap();
+        }
+
+        let parent = grandparent.next_helper(*parent_key).unwrap();
+        let subtree = parent.edges.remove(edge_key).unwrap();
+
+        // If the parent is left as a passthrough node, merge it with its remaining child
+        if !parent.is_key() && parent.edge_type() == Some(EdgeType::Single) {
+            let merge_key = *parent.edges.keys().next().unwrap();
+            grandparent.handle_passthrough(*parent_key, merge_key);
+        }
+
+        // Subtree's keys no longer count towards its ancestors
+        self.refresh_path(prefix);
+        Some((bytes, subtree))
+    }
+
+    // Creates a root node for a detached subtree, the subtree is relabeled with
+    // its full key bytes so that its keys remain intact
+    pub(crate) fn from_subtree((bytes, mut subtree): Subtree<K, V, A>) -> Node<K, V, A>
+    where A: TrieAggregate<V>
+    {
+        let mut root = Node::default();
+        let edge_key = bytes[0];
+
+        subtree.label.replace(bytes);
+        root.edges.insert(edge_key, subtree);
+        root.refresh();
+        root
+    }
+
     // Helper function to merge a passthrough node and its replacement to save space
     // Restores the tree's integrity after a delete by combining once separate labels
-    fn handle_passthrough(&mut self, edge_key: u8, merge_key: u8) -> Box<Node<K, V>> {
+    fn handle_passthrough(&mut self, edge_key: u8, merge_key: u8) -> Box<Node<K, V, A>> {
         let current = self;
 
         /*
@@ -338,24 +695,32 @@ impl<K, V> Node<K, V> {
 
 // Node functionality related to Iter
 
-impl<K, V> Node<K, V> {
-    pub(crate) fn iter(&self, size: usize) -> LeafPairsIter<'_, K, V> {
+impl<K, V, A> Node<K, V, A> {
+    pub(crate) fn iter(&self, size: usize) -> LeafPairsIter<'_, K, V, A> {
         LeafPairsIter::new(self, size)
     }
 
-    pub(crate) fn iter_mut(&mut self, size: usize) -> LeafPairsIterMut<'_, K, V> {
+    pub(crate) fn iter_mut(&mut self, size: usize) -> LeafPairsIterMut<'_, K, V, A> {
         LeafPairsIterMut::new(self, size)
     }
 
-    pub(crate) fn labels(&self, size: usize) -> LabelsIter<'_, K, V> {
+    pub(crate) fn labels(&self, size: usize) -> LabelsIter<'_, K, V, A> {
         LabelsIter::new(self, size)
     }
 
-    pub(crate) fn values(&self, size: usize) -> ValuesIter<'_, K, V> {
+    pub(crate) fn keys(&self, size: usize) -> KeysIter<'_, K, V, A> {
+        KeysIter::new(self, size)
+    }
+
+    pub(crate) fn pairs(&self, size: usize) -> PairsIter<'_, K, V, A> {
+        PairsIter::new(self, size)
+    }
+
+    pub(crate) fn values(&self, size: usize) -> ValuesIter<'_, K, V, A> {
         ValuesIter::new(self, size)
     }
 
-    pub(crate) fn values_mut(&mut self, size: usize) -> ValuesIterMut<'_, K, V> {
+    pub(crate) fn values_mut(&mut self, size: usize) -> ValuesIterMut<'_, K, V, A> {
         ValuesIterMut::new(self, size)
     }
 
@@ -364,7 +729,7 @@ impl<K, V> Node<K, V> {
     // when mostly used with iter when a node is being mutably borrowed,
     // and also to eliminate getter methods clutter
 
-    pub(crate) fn node_view(&self) -> NodeView<'_, K, V> {
+    pub(crate) fn node_view(&self) -> NodeView<'_, K, V, A> {
         NodeView::new(
             self.label.as_deref(),
             self.value.as_deref(),
@@ -373,7 +738,7 @@ impl<K, V> Node<K, V> {
         )
     }
 
-    pub(crate) fn node_view_mut(&mut self) -> NodeViewMut<'_, K, V> {
+    pub(crate) fn node_view_mut(&mut self) -> NodeViewMut<'_, K, V, A> {
         NodeViewMut::new(
             self.label.as_deref(),
             self.value.as_deref_mut(),
@@ -381,7 +746,7 @@ impl<K, V> Node<K, V> {
         )
     }
 
-    pub(crate) fn node_view_owned(mut self) -> NodeViewOwned<K, V> {
+    pub(crate) fn node_view_owned(mut self) -> NodeViewOwned<K, V, A> {
         NodeViewOwned::new(
             self.value.take().map(|b| *b),
             self.edges.into_values(),
@@ -389,12 +754,13 @@ impl<K, V> Node<K, V> {
     }
 }
 
-impl <K, V> IntoIterator for Node<K, V> {
-    type Item = V;
-    type IntoIter = IntoIter<K, V>;
+impl <K, V, A> IntoIterator for Node<K, V, A> {
+    type Item = (Vec<u8>, V);
+    type IntoIter = IntoIter<K, V, A>;
 
     fn into_iter(self) -> Self::IntoIter {
-        IntoIter::new(self)
+        let bytes = self.label().map_or_else(Vec::new, |l| l.to_vec());
+        IntoIter::new(self, bytes)
     }
 }
 
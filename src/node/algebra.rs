@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::mem;
+
+use crate::node::{Node, NodeType};
+use crate::aggregate::TrieAggregate;
+
+// Structural combination of two tries, both tries' nodes are walked in lockstep and
+// wherever the two compressed labels diverge, both labels are split at the divergence
+// point so that the two subtrees line up edge for edge. Once combined, each subtree is
+// compressed again to restore the trie's space optimized form
+
+// Determines which keys survive when two tries are combined
+// e.g. union keeps both sides, intersection keeps neither side (only the keys in common)
+pub(crate) struct Rules<F> {
+    pub(crate) keep_left: bool,  // keys only found in the left trie
+    pub(crate) keep_right: bool, // keys only found in the right trie
+    pub(crate) both: F,          // resolves keys found in both tries, None drops the key
+}
+
+impl<F> Rules<F> {
+    pub(crate) fn new(keep_left: bool, keep_right: bool, both: F) -> Self {
+        Rules {
+            keep_left,
+            keep_right,
+            both,
+        }
+    }
+}
+
+// Combines two root nodes (whose labels are both None), consuming both
+pub(crate) fn combine<K, V, A, F>(left: Node<K, V, A>, right: Node<K, V, A>, rules: &mut Rules<F>) -> Node<K, V, A>
+where F: FnMut(V, V) -> Option<V>, A: TrieAggregate<V>
+{
+    combine_nodes(left, right, rules)
+}
+
+// Combines two nodes which sit at the same position e.g. have identical labels
+fn combine_nodes<K, V, A, F>(mut left: Node<K, V, A>, mut right: Node<K, V, A>, rules: &mut Rules<F>) -> Node<K, V, A>
+where F: FnMut(V, V) -> Option<V>, A: TrieAggregate<V>
+{
+    let value = match (left.value.take(), right.value.take()) {
+        (Some(l), Some(r)) => (rules.both)(*l, *r).map(Box::new),
+        (Some(l), None) => Some(l).filter(|_| rules.keep_left),
+        (None, Some(r)) => Some(r).filter(|_| rules.keep_right),
+        (None, None) => None,
+    };
+
+    left.tag = if value.is_some() { NodeType::Key } else { NodeType::Inner };
+    left.value = value;
+
+    let left_edges = mem::take(&mut left.edges);
+    let mut right_edges = mem::take(&mut right.edges);
+
+    for (edge_key, l_child) in left_edges {
+        let child = match right_edges.remove(&edge_key) {
+            Some(r_child) => combine_edge(l_child, r_child, rules),
+            None if rules.keep_left => Some(l_child),
+            None => None,
+        };
+
+        if let Some(c) = child {
+            left.edges.insert(edge_key, c);
+        }
+    }
+
+    // Whatever is left over only resides in the right trie
+    if rules.keep_right {
+        left.edges.extend(right_edges);
+    }
+
+    left.refresh();
+    left
+}
+
+// Combines two child nodes that share the same edge key, though not necessarily the same label
+#[allow(clippy::boxed_local)]
+fn combine_edge<K, V, A, F>(mut left: Box<Node<K, V, A>>, mut right: Box<Node<K, V, A>>, rules: &mut Rules<F>) -> Option<Box<Node<K, V, A>>>
+where F: FnMut(V, V) -> Option<V>, A: TrieAggregate<V>
+{
+    let common = common_len(left.label().unwrap(), right.label().unwrap());
+
+    // Split the labels where they diverge so both nodes carry the same label
+    left.split_label(common);
+    right.split_label(common);
+
+    // Reuse the left node's allocation for the combined node
+    *left = combine_nodes(mem::take(&mut *left), mem::take(&mut *right), rules);
+    left.compress()
+}
+
+// Combines two borrowed root nodes, only the parts of either trie that survive are cloned.
+// Rather than splitting labels in place, each side is tracked as a node plus the part of
+// its label not yet matched, so e.g. intersecting with a small trie only walks its paths
+pub(crate) fn combine_ref<K, V, A, F>(left: &Node<K, V, A>, right: &Node<K, V, A>, rules: &mut Rules<F>) -> Node<K, V, A>
+where K: Clone, V: Clone, F: FnMut(V, V) -> Option<V>, A: TrieAggregate<V> + Clone
+{
+    combine_positions((left, &[]), (right, &[]), None, rules)
+}
+
+// A position within a borrowed trie, the node along with the remainder of its label still
+// ahead of the position (empty when the position is at the node itself)
+type Position<'a, K, V, A> = (&'a Node<K, V, A>, &'a [u8]);
+
+// Combines two positions that are reached by the same key, into a node with the given label
+fn combine_positions<K, V, A, F>(left: Position<'_, K, V, A>, right: Position<'_, K, V, A>, label: Option<Vec<u8>>, rules: &mut Rules<F>) -> Node<K, V, A>
+where K: Clone, V: Clone, F: FnMut(V, V) -> Option<V>, A: TrieAggregate<V> + Clone
+{
+    let value = match (value_at(left), value_at(right)) {
+        (Some(l), Some(r)) => (rules.both)(l.clone(), r.clone()),
+        (Some(l), None) => Some(l).filter(|_| rules.keep_left).cloned(),
+        (None, Some(r)) => Some(r).filter(|_| rules.keep_right).cloned(),
+        (None, None) => None,
+    };
+
+    let tag = if value.is_some() { NodeType::Key } else { NodeType::Inner };
+    let mut node = Node::new(label, tag, value.map(Box::new));
+
+    let mut right_edges = continuations(right).collect::<BTreeMap<_, _>>();
+
+    for (edge_key, l_next) in continuations(left) {
+        let child = match right_edges.remove(&edge_key) {
+            Some(r_next) => {
+                let common = common_len(l_next.1, r_next.1);
+                let label = l_next.1[..common].to_vec();
+                let l_next = (l_next.0, &l_next.1[common..]);
+                let r_next = (r_next.0, &r_next.1[common..]);
+
+                Box::new(combine_positions(l_next, r_next, Some(label), rules)).compress()
+            },
+            None if rules.keep_left => Some(clone_position(l_next)),
+            None => None,
+        };
+
+        if let Some(c) = child {
+            node.edges.insert(edge_key, c);
+        }
+    }
+
+    // Whatever is left over only resides in the right trie
+    if rules.keep_right {
+        node.edges.extend(right_edges.into_iter().map(|(k, r_next)| (k, clone_position(r_next))));
+    }
+
+    node.refresh();
+    node
+}
+
+// Returns the value at position, positions inside a label have no value
+fn value_at<'a, K, V, A>((node, rest): Position<'a, K, V, A>) -> Option<&'a V> {
+    node.value().filter(|_| rest.is_empty())
+}
+
+// Returns the positions one edge below position keyed by their edge key, a position
+// inside a label has the single position further along the same label
+fn continuations<'a, K, V, A>((node, rest): Position<'a, K, V, A>) -> impl Iterator<Item = (u8, Position<'a, K, V, A>)> {
+    let edges = node.edges.iter()
+        .filter(move |_| rest.is_empty())
+        .map(|(k, child)| (*k, (&**child, child.label().unwrap())));
+
+    rest.first().map(|k| (*k, (node, rest))).into_iter().chain(edges)
+}
+
+// Clones the subtree below position, labelled by the remainder of the position's label
+fn clone_position<K, V, A>((node, rest): Position<'_, K, V, A>) -> Box<Node<K, V, A>>
+where K: Clone, V: Clone, A: Clone
+{
+    let mut subtree = Box::new(node.clone());
+    subtree.label = Some(rest.to_vec());
+    subtree
+}
+
+// Structural split of a trie, moves all keys greater than or equal to token into a new node
+// which sits at the same position as node (and carries the same label), node retains the
+// keys that are less than token. Only subtrees straddling token's path are split apart,
+// edges off to either side of the path are moved wholesale
+pub(crate) fn split_off<K, V, A>(node: &mut Node<K, V, A>, token: &[u8]) -> Node<K, V, A>
+where A: TrieAggregate<V>
+{
+    let mut right = Node::new(node.label.clone(), NodeType::Inner, None);
+
+    // Node's key equals token, so node's key and all its descendants move
+    if token.is_empty() {
+        right.tag = mem::replace(&mut node.tag, NodeType::Inner);
+        right.value = node.value.take();
+        right.edges = mem::take(&mut node.edges);
+        right.refresh();
+        node.refresh();
+        return right
+    }
+
+    // Otherwise node's key is a proper prefix of token thus less than token and stays,
+    // edges that branch off after token's next byte hold greater keys and move
+    let greater = node.edges.keys().filter(|k| **k > token[0]).copied().collect::<Vec<u8>>();
+
+    for edge_key in greater {
+        right.edges.insert(edge_key, node.edges.remove(&edge_key).unwrap());
+    }
+
+    if let Some(mut child) = node.edges.remove(&token[0]) {
+        let label = child.label().unwrap();
+        let common = common_len(label, token);
+
+        if common == label.len() {
+            // Child's label lies on token's path, continue splitting below
+            let child_right = Box::new(split_off(&mut child, &token[common..]));
+
+            if let Some(c) = child.compress() {
+                node.edges.insert(token[0], c);
+            }
+
+            if let Some(c) = child_right.compress() {
+                right.edges.insert(token[0], c);
+            }
+        } else if common == token.len() || label[common] > token[common] {
+            // Token ends inside child's label or diverges below it, child's keys are all greater
+            right.edges.insert(token[0], child);
+        } else {
+            node.edges.insert(token[0], child);
+        }
+    }
+
+    right.refresh();
+    node.refresh();
+    right
+}
+
+#[inline]
+pub(crate) fn common_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(c1, c2)| c1 == c2).count()
+}
@@ -1,36 +1,36 @@
 use crate::node::Node;
 
-use std::collections::hash_map::{Keys, Values, ValuesMut, IntoValues};
+use std::collections::btree_map::{Keys, Values, ValuesMut, IntoValues};
 
 /*-----------------------------------------------------------------------------*/
 // Auxiliary data structures that provide views into Node mainly used by Iter,
 // generated when necessary
 
-pub struct NodeView<'a, K, V> {
+pub struct NodeView<'a, K, V, A> {
     pub(crate) label: Option<&'a [u8]>,
     pub(crate) value: Option<&'a V>,
-    pub(crate) edges: Values<'a, u8, Box<Node<K, V>>>,
-    pub(crate) keys: Keys<'a, u8, Box<Node<K, V>>>,
+    pub(crate) edges: Values<'a, u8, Box<Node<K, V, A>>>,
+    pub(crate) keys: Keys<'a, u8, Box<Node<K, V, A>>>,
 }
 
 // Borrow checker is smart enough to know that different struct fields can be re-borrowed (as mutable)
 // In that mutable access (a write) to one won't affect another
-pub struct NodeViewMut<'a, K, V> {
+pub struct NodeViewMut<'a, K, V, A> {
     pub(crate) label: Option<&'a [u8]>, // not allowed to modify label - just a shared ref
     pub(crate) value: Option<&'a mut V>,
-    pub(crate) edges: ValuesMut<'a, u8, Box<Node<K, V>>>,
+    pub(crate) edges: ValuesMut<'a, u8, Box<Node<K, V, A>>>,
 }
 
-pub struct NodeViewOwned<K, V> {
+pub struct NodeViewOwned<K, V, A> {
     pub(crate) value: Option<V>,
-    pub(crate) edges: IntoValues<u8, Box<Node<K, V>>>
+    pub(crate) edges: IntoValues<u8, Box<Node<K, V, A>>>
 }
 
 /*-----------------------------------------------------------------------------*/
 
-impl<'a, K, V> NodeView<'a, K, V> {
+impl<'a, K, V, A> NodeView<'a, K, V, A> {
     pub(super) fn new(label: Option<&'a [u8]>, value: Option<&'a V>,
-               edges: Values<'a, u8, Box<Node<K, V>>>, keys: Keys<'a, u8, Box<Node<K, V>>>) -> Self {
+               edges: Values<'a, u8, Box<Node<K, V, A>>>, keys: Keys<'a, u8, Box<Node<K, V, A>>>) -> Self {
         NodeView {
             label,
             value,
@@ -41,9 +41,9 @@ impl<'a, K, V> NodeView<'a, K, V> {
 }
 
 
-impl<'a, K, V> NodeViewMut<'a, K, V> {
+impl<'a, K, V, A> NodeViewMut<'a, K, V, A> {
     pub(super) fn new(label: Option<&'a [u8]>, value: Option<&'a mut V>,
-               edges: ValuesMut<'a, u8, Box<Node<K, V>>>) -> Self {
+               edges: ValuesMut<'a, u8, Box<Node<K, V, A>>>) -> Self {
         NodeViewMut {
             label,
             value,
@@ -52,8 +52,8 @@ impl<'a, K, V> NodeViewMut<'a, K, V> {
     }
 }
 
-impl<'a, K, V> NodeViewOwned<K, V> {
-    pub(super) fn new(value: Option<V>, edges: IntoValues<u8, Box<Node<K, V>>>) -> Self {
+impl<K, V, A> NodeViewOwned<K, V, A> {
+    pub(super) fn new(value: Option<V>, edges: IntoValues<u8, Box<Node<K, V, A>>>) -> Self {
         NodeViewOwned {
             value,
             edges,
@@ -0,0 +1,135 @@
+use std::borrow::Cow;
+
+use unicode_normalization::{UnicodeNormalization, IsNormalized, is_nfc_quick, is_nfkc_quick};
+
+// Key normalizers map equivalent spellings of a key onto a single form before the key
+// reaches the trie e.g. "Mou" and "mou", or "cafe\u{301}" and "café". Normalizers compose
+// as tuples, applied left to right, and other forms can be plugged in by implementing the trait
+
+pub trait Normalizer {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str>;
+}
+
+// Leaves keys untouched
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Identity;
+
+// Lowercases ASCII letters only
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AsciiCaseFold;
+
+// Unicode lowercasing, additionally folding ß to ss and final sigma to sigma
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CaseFold;
+
+// Unicode canonical composition (NFC), e.g. "e\u{301}" and "é" or U+212B and "Å" become one key
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Nfc;
+
+// Unicode compatibility composition (NFKC), additionally folding e.g. ligatures, fullwidth forms
+// and superscripts onto their plain forms
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Nfkc;
+
+// Applies f to each char if any char needs changing, otherwise borrows key
+fn map_chars<'a, F>(key: &'a str, needs: impl Fn(char) -> bool, mut f: F) -> Cow<'a, str>
+where F: FnMut(char, &mut String)
+{
+    if !key.chars().any(needs) {
+        return Cow::Borrowed(key)
+    }
+
+    let mut result = String::with_capacity(key.len());
+
+    for c in key.chars() {
+        f(c, &mut result);
+    }
+
+    Cow::Owned(result)
+}
+
+impl Normalizer for Identity {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(key)
+    }
+}
+
+impl Normalizer for AsciiCaseFold {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        map_chars(key, |c| c.is_ascii_uppercase(), |c, s| s.push(c.to_ascii_lowercase()))
+    }
+}
+
+impl Normalizer for CaseFold {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        let needs = |c: char| c == 'ß' || c == 'ς' || c.to_lowercase().ne(Some(c));
+
+        map_chars(key, needs, |c, s| match c {
+            'ß' => s.push_str("ss"),
+            'ς' => s.push('σ'),
+            _ => s.extend(c.to_lowercase()),
+        })
+    }
+}
+
+impl Normalizer for Nfc {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match is_nfc_quick(key.chars()) {
+            IsNormalized::Yes => Cow::Borrowed(key),
+            _ => Cow::Owned(key.nfc().collect()),
+        }
+    }
+}
+
+impl Normalizer for Nfkc {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match is_nfkc_quick(key.chars()) {
+            IsNormalized::Yes => Cow::Borrowed(key),
+            _ => Cow::Owned(key.nfkc().collect()),
+        }
+    }
+}
+
+// Applies the first normalizer then the second
+impl<N1: Normalizer, N2: Normalizer> Normalizer for (N1, N2) {
+    fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match self.0.normalize(key) {
+            Cow::Borrowed(k) => self.1.normalize(k),
+            Cow::Owned(k) => Cow::Owned(self.1.normalize(&k).into_owned()),
+        }
+    }
+}
+
+// Normalizer unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_case_fold() {
+        assert_eq!("mousepad", AsciiCaseFold.normalize("MouSEpad"));
+        assert_eq!("Éa", AsciiCaseFold.normalize("ÉA"));
+        assert_eq!("éa", CaseFold.normalize("ÉA"));
+        assert_eq!("strasse σοφοσ", CaseFold.normalize("STRAßE ΣΟΦΟΣ"));
+        assert!(matches!(CaseFold.normalize("plain"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn check_composition() {
+        assert_eq!("café", Nfc.normalize("cafe\u{301}"));
+        assert_eq!("Ångström", Nfc.normalize("A\u{30A}ngstro\u{308}m"));
+        assert_eq!("q\u{301}", Nfc.normalize("q\u{301}"));
+        assert!(matches!(Nfc.normalize("café"), Cow::Borrowed(_)));
+
+        // Singleton Angstrom sign maps to Å, and marks are put in canonical order first
+        assert_eq!("Å", Nfc.normalize("\u{212B}"));
+        assert_eq!(Nfc.normalize("a\u{323}\u{302}"), Nfc.normalize("a\u{302}\u{323}"));
+        assert_eq!("\u{1EAD}", Nfc.normalize("a\u{302}\u{323}"));
+        assert_eq!("\u{D55C}", Nfc.normalize("\u{1112}\u{1161}\u{11AB}"));
+
+        assert_eq!("file 12", Nfkc.normalize("\u{FB01}le\u{3000}\u{FF11}2"));
+        assert_eq!("fié", Nfkc.normalize("\u{FB01}e\u{301}"));
+        assert_eq!("café", (CaseFold, Nfc).normalize("CAFE\u{301}"));
+    }
+}
@@ -0,0 +1,202 @@
+use std::mem;
+
+use crate::trie::Trie;
+use crate::key::{encode_element, decode_element};
+
+// A trie keyed by delimited paths e.g. "/usr/lib", "bucket/logs/2024" or "cpu.load.avg", where
+// segments are atomic so prefix "/usr/li" doesn't match "/usr/lib". Each segment is stored as an
+// escaped and terminated element (as composite keys are), hence a segment prefix of the
+// encoding always ends on a segment boundary while labels still compress shared bytes.
+// Empty segments are ignored, so "/usr/lib" and "usr/lib/" name the same entry, and the
+// root path ("/" or "") encodes to the empty key
+
+#[derive(Clone, Debug)]
+pub struct PathTrie<V> {
+    trie: Trie<Vec<u8>, (String, V)>, // entries keep the path they were first inserted with
+    delimiter: char,
+}
+
+// Escape byte followed by the lowest byte above the element terminator, sorts after
+// every key within a segment's subtree and before any longer sibling segment
+const SUBTREE_END: [u8; 2] = [0x00, 0x02];
+
+impl<V> PathTrie<V> {
+    pub fn new(delimiter: char) -> Self {
+        PathTrie {
+            trie: Trie::new(),
+            delimiter,
+        }
+    }
+
+    pub fn delimiter(&self) -> char {
+        self.delimiter
+    }
+
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Inserts value, an existing entry for an equivalent path keeps its path
+    pub fn insert(&mut self, path: &str, value: V) -> Option<V> {
+        let key = self.encode(path);
+        let mut value = Some(value);
+        let (_, stored) = self.trie.get_or_insert_with_bytes(&key, || (path.to_string(), value.take().unwrap()));
+
+        value.map(|v| mem::replace(stored, v))
+    }
+
+    pub fn search(&self, path: &str) -> Option<&'_ V> {
+        self.trie.search(self.encode(path)).map(|(_, v)| v)
+    }
+
+    pub fn remove(&mut self, path: &str) -> Option<V> {
+        self.trie.remove(self.encode(path)).map(|(_, v)| v)
+    }
+
+    // Removes path along with every path beneath it, returning them as a trie
+    pub fn remove_subtree(&mut self, path: &str) -> PathTrie<V> {
+        PathTrie {
+            trie: self.trie.remove_prefix(self.encode(path)),
+            delimiter: self.delimiter,
+        }
+    }
+
+    // Lists the distinct segments directly beneath path which lead to an entry, in order
+    pub fn children(&self, path: &str) -> Vec<String> {
+        let prefix = self.encode(path);
+        let mut children = vec![];
+        let mut bound = prefix.clone();
+
+        // Each step jumps past the previous child's whole subtree
+        while let Some((key, _)) = self.trie.successor(bound) {
+            let Some((segment, _)) = key.strip_prefix(&prefix[..]).and_then(decode_element) else { break };
+
+            bound = prefix.clone();
+            encode_element(&mut bound, &segment);
+            bound.truncate(bound.len() - 2);
+            bound.extend_from_slice(&SUBTREE_END);
+
+            children.push(String::from_utf8_lossy(&segment).into_owned());
+        }
+
+        children
+    }
+
+    // Walks down from the root to path, yielding each entry along the way (including path
+    // itself) shortest first, e.g. to resolve settings inherited from parent directories
+    pub fn walk(&self, path: &str) -> impl Iterator<Item = (&'_ str, &'_ V)> {
+        self.trie.prefixes_of(self.encode(path)).map(|(_, (k, v))| (k.as_str(), v))
+    }
+
+    // Iterates path and every path beneath it, in segment order
+    pub fn descendants(&self, path: &str) -> impl Iterator<Item = (&'_ str, &'_ V)> {
+        self.trie.subtrie(self.encode(path)).iter_bytes().map(|(_, (k, v))| (k.as_str(), v))
+    }
+
+    // Iterates all paths in segment order, the root path first
+    pub fn iter(&self) -> impl Iterator<Item = (&'_ str, &'_ V)> {
+        self.trie.values().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn clear(&mut self) {
+        self.trie.clear();
+    }
+
+    fn encode(&self, path: &str) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(path.len() + 2);
+
+        for segment in path.split(self.delimiter).filter(|s| !s.is_empty()) {
+            encode_element(&mut bytes, segment.as_bytes());
+        }
+
+        bytes
+    }
+}
+
+impl<V> Default for PathTrie<V> {
+    fn default() -> Self {
+        PathTrie::new('/')
+    }
+}
+
+// PathTrie unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths_helper<'a>(iter: impl Iterator<Item = (&'a str, &'a i32)>) -> Vec<&'a str> {
+        iter.map(|(k, _)| k).collect()
+    }
+
+    #[test]
+    fn check_path_segments() {
+        let mut trie = PathTrie::default();
+
+        for (i, path) in ["/usr", "/usr/lib", "/usr/lib/x86", "/usr/libexec", "/usr/local/bin", "/var/log", "/usr/li"].iter().enumerate() {
+            trie.insert(path, i as i32);
+        }
+
+        assert_eq!(Some(&6), trie.search("usr/li/"));
+        assert_eq!(None, trie.search("/usr/local"));
+        assert_eq!(vec!["/usr", "/usr/li"], paths_helper(trie.walk("/usr/li/b")));
+        assert_eq!(vec!["/usr", "/usr/lib", "/usr/lib/x86"], paths_helper(trie.walk("/usr/lib/x86")));
+        assert_eq!(vec!["/usr/lib", "/usr/lib/x86"], paths_helper(trie.descendants("/usr/lib")));
+
+        assert_eq!(vec!["usr", "var"], trie.children("/"));
+        assert_eq!(vec!["li", "lib", "libexec", "local"], trie.children("/usr"));
+        assert!(trie.children("/usr/local/bin").is_empty());
+
+        let removed = trie.remove_subtree("/usr/li");
+        assert_eq!(vec!["/usr/li"], paths_helper(removed.iter()));
+        assert_eq!(6, trie.len());
+
+        let removed = trie.remove_subtree("/usr/lib");
+        assert_eq!(2, removed.len());
+        assert_eq!(vec!["/usr", "/usr/libexec", "/usr/local/bin", "/var/log"], paths_helper(trie.iter()));
+    }
+
+    #[test]
+    fn check_dotted_names() {
+        let mut trie = PathTrie::new('.');
+
+        trie.insert("cpu.load.avg", 1);
+        trie.insert("cpu.load", 2);
+        trie.insert("cpu.loadavg", 3);
+        trie.insert("cpu\0.x", 4);
+
+        assert_eq!(vec!["cpu", "cpu\0"], trie.children(""));
+        assert_eq!(vec!["load", "loadavg"], trie.children("cpu"));
+        assert_eq!(Some(2), trie.remove("cpu.load"));
+        assert_eq!(vec!["load", "loadavg"], trie.children("cpu"));
+    }
+
+    #[test]
+    fn check_root_path() {
+        let mut trie = PathTrie::default();
+
+        trie.insert("/etc", 1);
+        assert_eq!(None, trie.insert("/", 0));
+        assert_eq!(Some(0), trie.insert("", 2));
+        assert_eq!(2, trie.len());
+
+        assert_eq!(Some(&2), trie.search("//"));
+        assert_eq!(vec!["/", "/etc"], paths_helper(trie.walk("/etc/hosts")));
+        assert_eq!(vec!["/"], paths_helper(trie.walk("/var")));
+        assert_eq!(vec!["/", "/etc"], paths_helper(trie.descendants("/")));
+        assert_eq!(vec!["/etc"], paths_helper(trie.descendants("/etc")));
+        assert_eq!(vec!["/", "/etc"], paths_helper(trie.iter()));
+
+        assert_eq!(Some(2), trie.remove("/"));
+        assert_eq!(vec!["/etc"], paths_helper(trie.walk("/etc")));
+
+        trie.insert("/", 3);
+        let removed = trie.remove_subtree("");
+        assert_eq!(vec!["/", "/etc"], paths_helper(removed.iter()));
+        assert!(trie.is_empty());
+    }
+}
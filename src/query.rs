@@ -1,13 +1,14 @@
 use std::collections::VecDeque;
 
-use crate::node::{Node};
-use crate::traverse::{TraverseItem, TraverseType, TraverseResult, traverse};
+use crate::node::{Node, KeyedNode};
+use crate::aggregate::TrieAggregate;
+use crate::traverse::{TraverseItem, TraverseStack, TraverseType, TraverseResult, KeyMatch, SuffixType, traverse, traverse_match};
 use crate::macros::enum_extract;
 
 // Finds the longest path that corresponds to the prefix token, one that fully captures
 // the token or part of it (should it not fully reside in trie) and return it as an iterator
-pub fn longest_prefix<'a, 'b, K, V>(node: &'a Node<K, V>, prefix: &'b [u8]) -> Option<impl Iterator<Item = &'a u8>> { // Option<String> {
-    let value: TraverseResult<K, V> =  traverse(node, prefix, TraverseType::FoldOrPartial)?;
+pub fn longest_prefix<'a, K, V, A>(node: &'a Node<K, V, A>, prefix: &[u8]) -> Option<impl Iterator<Item = &'a u8>> { // Option<String> {
+    let value: TraverseResult<K, V, A> =  traverse(node, prefix, TraverseType::FoldOrPartial)?;
     let mut stack = enum_extract!(value, TraverseResult::Stack);
 
     // store label iterators
@@ -20,16 +21,16 @@ pub fn longest_prefix<'a, 'b, K, V>(node: &'a Node<K, V>, prefix: &'b [u8]) -> O
 
     let mut last_label;
 
-    while !stack.is_empty() {
-        let TraverseItem{node, next_key: _, label, level} = stack.pop().unwrap();
+    while let Some(TraverseItem{node, next_key: _, label, level}) = stack.pop() {
 
         last_label = label;
 
         if node.is_key() {
             prefixes = Vec::with_capacity(level as usize);
 
-            // Ignore root label so start with 1
-            prefixes = stack.drain(1..).fold(prefixes, |mut acc, TraverseItem{node: _, next_key: _, label, level: _}| {
+            // Ignore root label so start with 1, the root itself may hold the empty key
+            let start = stack.len().min(1);
+            prefixes = stack.drain(start..).fold(prefixes, |mut acc, TraverseItem{node: _, next_key: _, label, level: _}| {
                 if let Some(lab) = label {
                     acc.push(lab.iter());
                     //acc.extend(label.unwrap().to_owned())
@@ -38,9 +39,10 @@ pub fn longest_prefix<'a, 'b, K, V>(node: &'a Node<K, V>, prefix: &'b [u8]) -> O
             );
 
             // Add in last label of key node
-            prefixes.push(last_label.unwrap().iter());
+            prefixes.push(last_label.unwrap_or_default().iter());
             let p = prefixes.into_iter().flatten();
             result = Some(p);
+            break
 
             //prefixes.extend(last_label.unwrap().to_owned());
             //result = Some(prefixes)
@@ -50,10 +52,132 @@ pub fn longest_prefix<'a, 'b, K, V>(node: &'a Node<K, V>, prefix: &'b [u8]) -> O
     result
 }
 
+// Finds the longest key that is a prefix of token in a single traversal,
+// returning the key's length along with its value
+pub fn longest_prefix_match<'a, K, V, A>(node: &'a Node<K, V, A>, token: &[u8]) -> Option<(usize, &'a V)> {
+    longest_prefix_walk(node, token).0
+}
+
+// Walks token's path without building a traversal stack, recording the deepest key node
+// encountered. Also reports whether token ran out while its path could still continue,
+// in which case a longer token could have a longer match
+pub fn longest_prefix_walk<'a, K, V, A>(node: &'a Node<K, V, A>, token: &[u8]) -> (Option<(usize, &'a V)>, bool) {
+    let mut current: &Node<K, V, A> = node;
+    let mut nav_token: &[u8] = token;
+    let mut len = 0;
+    let mut result = node.value().map(|v| (0, v));
+
+    while !nav_token.is_empty() {
+        match traverse_match(current, nav_token) {
+            Some(KeyMatch {next, common, leftover: SuffixType::Empty, ..}) => {
+                len += common.len();
+                result = next.value().map(|v| (len, v)).or(result);
+                return (result, next.edge_type().is_some())
+            },
+            Some(KeyMatch {next, common, leftover: SuffixType::OnlyToken(sufx), ..}) => {
+                len += common.len();
+                result = next.value().map(|v| (len, v)).or(result);
+                current = next;
+                nav_token = sufx;
+            },
+            // Token ends in the middle of a label
+            Some(KeyMatch {leftover: SuffixType::OnlyEdge(_), ..}) => return (result, true),
+            // Token diverges from the trie
+            _ => return (result, false),
+        }
+    }
+
+    (result, current.edge_type().is_some())
+}
+
+// Finds the shortest key that is a prefix of token, returning the key's length along with
+// its value. Stops descending as soon as the first key node on token's path is reached
+pub fn shortest_prefix<'a, K, V, A>(node: &'a Node<K, V, A>, token: &[u8]) -> Option<(usize, &'a V)> {
+    let mut current: &Node<K, V, A> = node;
+    let mut nav_token: &[u8] = token;
+    let mut len = 0;
+
+    // The empty key prefixes every token
+    if let Some(v) = node.value() {
+        return Some((0, v))
+    }
+
+    while !nav_token.is_empty() {
+        match traverse_match(current, nav_token)? {
+            KeyMatch {next, common, leftover: SuffixType::Empty, ..} => {
+                len += common.len();
+                return next.value().map(|v| (len, v))
+            },
+            KeyMatch {next, common, leftover: SuffixType::OnlyToken(sufx), ..} => {
+                len += common.len();
+
+                if let Some(v) = next.value() {
+                    return Some((len, v))
+                }
+
+                current = next;
+                nav_token = sufx;
+            },
+            // Token ends or diverges in the middle of a label
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+// Lazily yields every key which is a prefix of a token, in increasing length order.
+// Each item is the key's length (the key being token[..length]) along with its value
+#[derive(Debug)]
+pub struct PrefixesIter<'a, K, V, A = ()> {
+    stack: std::vec::IntoIter<TraverseItem<'a, K, V, A>>,
+    len: usize,
+}
+
+impl<'a, K, V, A> PrefixesIter<'a, K, V, A> {
+    // Partial fold stack of token's path, the stack runs from the start node downwards
+    fn new(stack: TraverseStack<'a, K, V, A>) -> Self {
+        PrefixesIter {
+            stack: stack.into_iter(),
+            len: 0,
+        }
+    }
+}
+
+impl<K, V, A> Default for PrefixesIter<'_, K, V, A> {
+    fn default() -> Self {
+        PrefixesIter::new(vec![])
+    }
+}
+
+impl<'a, K, V, A> Iterator for PrefixesIter<'a, K, V, A> {
+    type Item = (usize, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for TraverseItem{node, next_key: _, label, level: _} in self.stack.by_ref() {
+            self.len += label.map_or(0, |l| l.len());
+
+            if let Some(v) = node.value() {
+                return Some((self.len, v))
+            }
+        }
+
+        None
+    }
+}
+
+// Finds all the keys which are prefixes of token (a common prefix search)
+pub fn prefixes_of<'a, K, V, A>(node: &'a Node<K, V, A>, token: &[u8]) -> PrefixesIter<'a, K, V, A> {
+    match traverse(node, token, TraverseType::FoldOrPartial) {
+        Some(value) => PrefixesIter::new(enum_extract!(value, TraverseResult::Stack)),
+        None => PrefixesIter::default(),
+    }
+}
+
 // Find all prefix keys which have the same common prefix
-pub fn all_keys<K, V>(node: &Node<K, V>, prefix: &[u8]) -> Option<Vec<Vec<u8>>> {
+pub fn all_keys<K, V, A>(node: &Node<K, V, A>, prefix: &[u8]) -> Option<Vec<Vec<u8>>> {
     // Grab node where the prefix search ends
-    let result: TraverseResult<K, V> = traverse(node, prefix, TraverseType::Search)?;
+    let result: TraverseResult<K, V, A> = traverse(node, prefix, TraverseType::Search)?;
 
     // If prefix is contained in the middle of a label e.g. partial terminal, that's fine
     // Just take that terminal node's label which is prefix + edge_suffix as the
@@ -71,9 +195,9 @@ pub fn all_keys<K, V>(node: &Node<K, V>, prefix: &[u8]) -> Option<Vec<Vec<u8>>>
         };
 
     let mut result: Vec<Vec<u8>> = Vec::new();
-    let mut backlog: VecDeque<(&Node<K, V>, Vec<u8>)> = VecDeque::new();
+    let mut backlog: VecDeque<KeyedNode<K, V, A>> = VecDeque::new();
 
-    let mut child: &Node<K, V>;
+    let mut child: &Node<K, V, A>;
     let mut child_bytes: Vec<u8>;
     let mut label_slice: &[u8];
 
@@ -116,3 +240,89 @@ pub fn all_keys<K, V>(node: &Node<K, V>, prefix: &[u8]) -> Option<Vec<Vec<u8>>>
 
     Some(result)
 }
+
+// Counts the keys strictly less than token (token need not be stored). Walks token's path
+// adding the key counts of edges branching off before it, along with any key node on
+// the path which is a proper prefix of token
+pub fn rank<K, V, A>(node: &Node<K, V, A>, token: &[u8]) -> usize {
+    let mut current: &Node<K, V, A> = node;
+    let mut nav_token: &[u8] = token;
+    let mut rank = 0;
+
+    while !nav_token.is_empty() {
+        rank += current.is_key() as usize + current.count_before(nav_token[0]);
+
+        match traverse_match(current, nav_token) {
+            Some(KeyMatch {next, common: _, leftover: SuffixType::OnlyToken(sufx), ..}) => {
+                current = next;
+                nav_token = sufx;
+            },
+            // Token diverges inside the label, the subtree is entirely less than token
+            Some(KeyMatch {next, common: _, leftover: SuffixType::BothEdgeToken(e, t), ..}) if e[0] < t[0] => {
+                rank += next.key_count();
+                break
+            },
+            // Otherwise the remaining keys are all greater than or equal to token
+            _ => break,
+        }
+    }
+
+    rank
+}
+
+// Finds the key at position n in lexicographic order (zero based), descending
+// into the edge whose key count range contains n
+pub fn select<K, V, A>(node: &Node<K, V, A>, mut n: usize) -> Option<(Vec<u8>, &V)> {
+    let mut current: &Node<K, V, A> = node;
+    let mut bytes: Vec<u8> = Vec::new();
+
+    if n >= node.key_count() {
+        return None
+    }
+
+    loop {
+        if current.is_key() {
+            if n == 0 {
+                return current.value().map(|v| (bytes, v))
+            }
+
+            n -= 1;
+        }
+
+        let view = current.node_view();
+        let mut next = None;
+
+        for child in view.edges {
+            if n < child.key_count() {
+                next = Some(&**child);
+                break
+            }
+
+            n -= child.key_count();
+        }
+
+        current = next?;
+        bytes.extend_from_slice(current.label().unwrap());
+    }
+}
+
+// Counts the keys which start with prefix
+pub fn count_prefix<K, V, A>(node: &Node<K, V, A>, prefix: &[u8]) -> usize {
+    match traverse(node, prefix, TraverseType::Search) {
+        Some(TraverseResult::Terminal(_, n) | TraverseResult::PartialTerminal(_, n, _)) => n.key_count(),
+        _ => 0,
+    }
+}
+
+// Combines the summaries of the values of all keys which start with prefix, the summary cached
+// at the node where prefix ends covers exactly those keys
+pub fn aggregate_prefix<K, V, A>(node: &Node<K, V, A>, prefix: &[u8]) -> A
+where A: TrieAggregate<V> + Clone
+{
+    let current = match traverse(node, prefix, TraverseType::Search) {
+        Some(TraverseResult::Terminal(_, n) | TraverseResult::PartialTerminal(_, n, _)) => n,
+        _ => return A::empty(),
+    };
+
+    current.summary().cloned().unwrap_or_else(A::empty)
+}
@@ -0,0 +1,311 @@
+use std::fmt;
+
+use crate::trie::Trie;
+use crate::query::prefixes_of;
+
+// URL router, route patterns are made of static text, named parameters (":id") matching
+// a single path segment and a trailing catch-all wildcard ("*path") matching the rest of the path.
+// Each run of static text between parameters is a key in a radix trie, so static parts share
+// compressed labels and are matched through the trie's label splitting. A lookup prefers
+// static matches over parameters and parameters over wildcards, backtracking when a
+// preferred branch fails further along the path
+
+#[derive(Clone, Debug)]
+pub struct Router<V> {
+    root: RouteNode<V>,
+    size: usize,
+}
+
+// A position in a pattern following a static run or a parameter
+#[derive(Clone, Debug)]
+struct RouteNode<V> {
+    statics: Trie<Vec<u8>, RouteNode<V>>, // static runs that may follow
+    param: Option<Box<RouteNode<V>>>,     // parameter that may follow
+    wildcard: Option<Route<V>>,           // route ending in a catch-all here
+    route: Option<Route<V>>,              // route ending here
+}
+
+#[derive(Clone, Debug)]
+struct Route<V> {
+    pattern: String,
+    names: Vec<String>, // parameter and wildcard names in order
+    value: V,
+}
+
+// Result of a successful lookup
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteMatch<'r, 'p, V> {
+    pub pattern: &'r str,
+    pub value: &'r V,
+    pub params: Vec<(&'r str, &'p str)>, // captured name and path text, in pattern order
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteError {
+    pattern: String,
+    reason: &'static str,
+}
+
+enum Part<'a> {
+    Static(&'a str),
+    Param(&'a str),
+    Wildcard(&'a str),
+}
+
+impl<V> RouteNode<V> {
+    fn new() -> Self {
+        RouteNode {
+            statics: Trie::new(),
+            param: None,
+            wildcard: None,
+            route: None,
+        }
+    }
+
+    // Returns the node following static run, creating it if absent
+    fn static_child(&mut self, run: &str) -> &mut RouteNode<V> {
+        self.statics.get_or_insert_with_bytes(run.as_bytes(), RouteNode::new)
+    }
+
+    // Finds the route matching path, pushing captured text onto captures
+    fn find<'r, 'p>(&'r self, path: &'p str, captures: &mut Vec<&'p str>) -> Option<&'r Route<V>> {
+        if path.is_empty() {
+            if let Some(route) = self.route.as_ref() {
+                return Some(route)
+            }
+        }
+
+        // Static runs which prefix path, the longest being the most specific
+        if let Some(root) = self.statics.root() {
+            let matches = prefixes_of(root, path.as_bytes()).collect::<Vec<_>>();
+
+            for (len, child) in matches.into_iter().rev() {
+                if let Some(route) = child.find(&path[len..], captures) {
+                    return Some(route)
+                }
+            }
+        }
+
+        // Parameter captures a non empty segment
+        if let Some(child) = self.param.as_deref() {
+            let end = path.find('/').unwrap_or(path.len());
+
+            if end > 0 {
+                captures.push(&path[..end]);
+
+                if let Some(route) = child.find(&path[end..], captures) {
+                    return Some(route)
+                }
+
+                captures.pop();
+            }
+        }
+
+        let route = self.wildcard.as_ref()?;
+        captures.push(path);
+        Some(route)
+    }
+
+    fn routes(&self) -> Vec<&Route<V>> {
+        let mut routes = vec![];
+
+        routes.extend(self.route.as_ref());
+        self.statics.values().for_each(|n| routes.extend(n.routes()));
+        self.param.iter().for_each(|n| routes.extend(n.routes()));
+        routes.extend(self.wildcard.as_ref());
+
+        routes
+    }
+}
+
+// Splits pattern into static runs, parameters and a wildcard. Parameters and
+// wildcards must span a whole segment, and a wildcard must end the pattern
+fn parse(pattern: &str) -> Result<Vec<Part<'_>>, RouteError> {
+    let error = |reason| RouteError { pattern: pattern.to_string(), reason };
+    let mut parts = vec![];
+    let mut rest = pattern;
+
+    while !rest.is_empty() {
+        let start = rest.find([':', '*']).unwrap_or(rest.len());
+
+        if start > 0 {
+            parts.push(Part::Static(&rest[..start]));
+            rest = &rest[start..];
+            continue
+        }
+
+        if !pattern[..pattern.len() - rest.len()].ends_with('/') {
+            return Err(error("parameter must start a segment"))
+        }
+
+        let end = rest.find('/').unwrap_or(rest.len());
+        let name = &rest[1..end];
+
+        if name.is_empty() || name.contains([':', '*']) {
+            return Err(error("invalid parameter name"))
+        }
+
+        if rest.starts_with('*') {
+            if end != rest.len() {
+                return Err(error("wildcard must end the pattern"))
+            }
+
+            parts.push(Part::Wildcard(name));
+        } else {
+            parts.push(Part::Param(name));
+        }
+
+        rest = &rest[end..];
+    }
+
+    Ok(parts)
+}
+
+impl<V> Router<V> {
+    pub fn new() -> Self {
+        Router {
+            root: RouteNode::new(),
+            size: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    // Adds a route, returning the value of any route it replaces. Patterns differing
+    // only in parameter names e.g. "/users/:id" and "/users/:name" are the same route
+    pub fn insert(&mut self, pattern: &str, value: V) -> Result<Option<V>, RouteError> {
+        let parts = parse(pattern)?;
+        let mut names = vec![];
+        let mut node = &mut self.root;
+        let mut wildcard = false;
+
+        for part in parts {
+            match part {
+                Part::Static(run) => node = node.static_child(run),
+                Part::Param(name) => {
+                    names.push(name.to_string());
+                    node = node.param.get_or_insert_with(|| Box::new(RouteNode::new()));
+                },
+                Part::Wildcard(name) => {
+                    names.push(name.to_string());
+                    wildcard = true;
+                },
+            }
+        }
+
+        let route = Route { pattern: pattern.to_string(), names, value };
+        let slot = if wildcard { &mut node.wildcard } else { &mut node.route };
+        let old = slot.replace(route).map(|r| r.value);
+
+        if old.is_none() {
+            self.size += 1;
+        }
+
+        Ok(old)
+    }
+
+    // Finds the route matching path along with the parameters it captures
+    pub fn lookup<'r, 'p>(&'r self, path: &'p str) -> Option<RouteMatch<'r, 'p, V>> {
+        let mut captures = vec![];
+        let route = self.root.find(path, &mut captures)?;
+
+        Some(RouteMatch {
+            pattern: &route.pattern,
+            value: &route.value,
+            params: route.names.iter().map(String::as_str).zip(captures).collect(),
+        })
+    }
+
+    // Iterates route patterns and values, static routes ahead of parameterized ones
+    pub fn iter(&self) -> impl Iterator<Item = (&'_ str, &'_ V)> {
+        self.root.routes().into_iter().map(|r| (r.pattern.as_str(), &r.value))
+    }
+}
+
+impl<V> Default for Router<V> {
+    fn default() -> Self {
+        Router::new()
+    }
+}
+
+impl<V> RouteMatch<'_, '_, V> {
+    // Returns the captured text for the named parameter or wildcard
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid route {:?}: {}", self.pattern, self.reason)
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+// Router unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router() -> Router<i32> {
+        let mut router = Router::new();
+
+        for (i, pattern) in ["/", "/users", "/users/:id", "/users/new", "/users/:id/posts/:post",
+                             "/users/:id/settings", "/users/admin/settings", "/static/*path", "/static/favicon.ico"].iter().enumerate() {
+            assert_eq!(Ok(None), router.insert(pattern, i as i32));
+        }
+
+        router
+    }
+
+    #[test]
+    fn check_route_lookup() {
+        let router = router();
+
+        let m = router.lookup("/users/42/posts/7").unwrap();
+        assert_eq!("/users/:id/posts/:post", m.pattern);
+        assert_eq!(vec![("id", "42"), ("post", "7")], m.params);
+        assert_eq!(Some("7"), m.param("post"));
+
+        // static segments take priority over parameters
+        assert_eq!(3, *router.lookup("/users/new").unwrap().value);
+        assert_eq!(2, *router.lookup("/users/newer").unwrap().value);
+        assert_eq!(8, *router.lookup("/static/favicon.ico").unwrap().value);
+
+        // "/users/new" matches statically but leads nowhere further, backtrack to :id
+        let m = router.lookup("/users/new/settings").unwrap();
+        assert_eq!(("/users/:id/settings", Some("new")), (m.pattern, m.param("id")));
+        assert_eq!(6, *router.lookup("/users/admin/settings").unwrap().value);
+        assert_eq!(Some("admin"), router.lookup("/users/admin/posts/1").unwrap().param("id"));
+
+        assert_eq!(Some("css/site.css"), router.lookup("/static/css/site.css").unwrap().param("path"));
+        assert_eq!(Some(""), router.lookup("/static/").unwrap().param("path"));
+        assert_eq!(0, *router.lookup("/").unwrap().value);
+        assert!(router.lookup("/users/").is_none());
+        assert!(router.lookup("/userss").is_none());
+        assert!(router.lookup("/users/42/").is_none());
+    }
+
+    #[test]
+    fn check_route_insert() {
+        let mut router = router();
+
+        assert_eq!(9, router.len());
+        assert_eq!(Ok(Some(2)), router.insert("/users/:name", 20));
+        assert_eq!(Some("42"), router.lookup("/users/42").unwrap().param("name"));
+        assert_eq!(9, router.iter().count());
+
+        assert!(router.insert("/files/*path/edit", 0).is_err());
+        assert!(router.insert("/users:id", 0).is_err());
+        assert!(router.insert("/users/:", 0).is_err());
+        assert_eq!(9, router.len());
+    }
+}
@@ -0,0 +1,291 @@
+use std::fmt;
+use std::str::FromStr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::bits::{BitTrie, MatchesIter, CoveredIter};
+
+// Routing table mapping CIDR blocks to values, IPv4 and IPv6 prefixes are
+// kept in separate bit tries as their address spaces don't overlap
+
+#[derive(Clone, Debug)]
+pub struct IpRoutingTable<V> {
+    v4: BitTrie<V>,
+    v6: BitTrie<V>,
+}
+
+// An address block e.g. 10.0.0.0/8, host bits past the prefix length are always zeroed
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IpCidr {
+    addr: IpAddr,
+    len: u8,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CidrParseError;
+
+impl IpCidr {
+    // Returns None if len exceeds the address' bit length
+    pub fn new(addr: IpAddr, len: u8) -> Option<IpCidr> {
+        let (mut octets, max) = octets(addr);
+
+        if len as usize > max {
+            return None
+        }
+
+        // Zero the host bits
+        for (i, b) in octets.iter_mut().enumerate() {
+            let keep = (len as usize).saturating_sub(i * 8).min(8);
+            *b &= !(0xFFu16 >> keep) as u8;
+        }
+
+        Some(IpCidr { addr: from_octets(addr, &octets), len })
+    }
+
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.len
+    }
+
+    // Whether addr falls within the block
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        IpCidr::new(addr, self.len).is_some_and(|c| c == *self)
+    }
+
+    // Builds cidr from bit trie key bytes, which only hold the prefix's bytes
+    fn from_bits(template: IpAddr, bytes: &[u8], len: usize) -> IpCidr {
+        let (mut octets, _) = octets(template);
+
+        octets.fill(0);
+        octets[..bytes.len()].copy_from_slice(bytes);
+
+        IpCidr { addr: from_octets(template, &octets), len: len as u8 }
+    }
+}
+
+// Returns address octets (padded to 16 bytes) along with the address' bit length
+fn octets(addr: IpAddr) -> ([u8; 16], usize) {
+    let mut bytes = [0; 16];
+
+    match addr {
+        IpAddr::V4(a) => {
+            bytes[..4].copy_from_slice(&a.octets());
+            (bytes, 32)
+        },
+        IpAddr::V6(a) => (a.octets(), 128),
+    }
+}
+
+fn from_octets(template: IpAddr, bytes: &[u8; 16]) -> IpAddr {
+    match template {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(*bytes)),
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = CidrParseError;
+
+    // Parses "addr/len", a bare address is taken as a single host block
+    fn from_str(s: &str) -> Result<IpCidr, CidrParseError> {
+        let (addr, len) = match s.split_once('/') {
+            Some((addr, len)) => (addr, Some(len)),
+            None => (s, None),
+        };
+
+        let addr: IpAddr = addr.parse().map_err(|_| CidrParseError)?;
+        let len = match len {
+            Some(len) => len.parse().map_err(|_| CidrParseError)?,
+            None => octets(addr).1 as u8,
+        };
+
+        IpCidr::new(addr, len).ok_or(CidrParseError)
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.len)
+    }
+}
+
+impl fmt::Display for CidrParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid CIDR block")
+    }
+}
+
+impl std::error::Error for CidrParseError {}
+
+impl<V> IpRoutingTable<V> {
+    pub fn new() -> Self {
+        IpRoutingTable {
+            v4: BitTrie::new(),
+            v6: BitTrie::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.v4.len() + self.v6.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Inserts a route for the block, returning the block's previous value if any
+    pub fn insert(&mut self, cidr: IpCidr, value: V) -> Option<V> {
+        let (bytes, max) = octets(cidr.addr);
+        self.table_mut(cidr.addr).insert(&bytes[..max / 8], cidr.len as usize, value)
+    }
+
+    // Retrieves the value stored for exactly the block
+    pub fn get(&self, cidr: IpCidr) -> Option<&V> {
+        let (bytes, max) = octets(cidr.addr);
+        self.table(cidr.addr).get(&bytes[..max / 8], cidr.len as usize)
+    }
+
+    pub fn remove(&mut self, cidr: IpCidr) -> Option<V> {
+        let (bytes, max) = octets(cidr.addr);
+        self.table_mut(cidr.addr).remove(&bytes[..max / 8], cidr.len as usize)
+    }
+
+    // Finds the most specific block containing addr
+    pub fn longest_match(&self, addr: IpAddr) -> Option<(IpCidr, &V)> {
+        self.covering_prefixes(addr).last()
+    }
+
+    // Iterates all blocks containing addr, least specific first
+    pub fn covering_prefixes(&self, addr: IpAddr) -> CoveringIter<'_, V> {
+        let (bytes, max) = octets(addr);
+
+        CoveringIter {
+            iter: self.table(addr).matches(&bytes[..max / 8], max),
+            addr,
+        }
+    }
+
+    // Iterates all blocks within cidr (including cidr itself), in address order
+    pub fn covered_by(&self, cidr: IpCidr) -> CoveredByIter<'_, V> {
+        let (bytes, max) = octets(cidr.addr);
+
+        CoveredByIter {
+            iter: self.table(cidr.addr).covered_by(&bytes[..max / 8], cidr.len as usize),
+            addr: cidr.addr,
+        }
+    }
+
+    // Iterates all IPv4 blocks followed by all IPv6 blocks, each in address order
+    pub fn iter(&self) -> impl Iterator<Item = (IpCidr, &'_ V)> {
+        let v4 = CoveredByIter { iter: self.v4.iter(), addr: Ipv4Addr::UNSPECIFIED.into() };
+        let v6 = CoveredByIter { iter: self.v6.iter(), addr: Ipv6Addr::UNSPECIFIED.into() };
+        v4.chain(v6)
+    }
+
+    fn table(&self, addr: IpAddr) -> &BitTrie<V> {
+        if addr.is_ipv4() { &self.v4 } else { &self.v6 }
+    }
+
+    fn table_mut(&mut self, addr: IpAddr) -> &mut BitTrie<V> {
+        if addr.is_ipv4() { &mut self.v4 } else { &mut self.v6 }
+    }
+}
+
+impl<V> Default for IpRoutingTable<V> {
+    fn default() -> Self {
+        IpRoutingTable::new()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CoveringIter<'a, V> {
+    iter: MatchesIter<'a, V>,
+    addr: IpAddr,
+}
+
+impl<'a, V> Iterator for CoveringIter<'a, V> {
+    type Item = (IpCidr, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (len, v) = self.iter.next()?;
+        IpCidr::new(self.addr, len as u8).map(|c| (c, v))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CoveredByIter<'a, V> {
+    iter: CoveredIter<'a, V>,
+    addr: IpAddr, // template for the address family
+}
+
+impl<'a, V> Iterator for CoveredByIter<'a, V> {
+    type Item = (IpCidr, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (bytes, len, v) = self.iter.next()?;
+        Some((IpCidr::from_bits(self.addr, bytes, len), v))
+    }
+}
+
+// IpRoutingTable unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cidr(s: &str) -> IpCidr {
+        s.parse().unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn check_cidr_parse() {
+        assert_eq!("10.0.0.0/8", cidr("10.1.2.3/8").to_string());
+        assert_eq!("10.128.0.0/9", cidr("10.200.2.3/9").to_string());
+        assert_eq!("2001:db8::/32", cidr("2001:db8:ffff::1/32").to_string());
+        assert_eq!(32, cidr("1.2.3.4").prefix_len());
+        assert_eq!(Err(CidrParseError), "10.0.0.0/33".parse::<IpCidr>());
+        assert_eq!(Err(CidrParseError), "10.0.0/8".parse::<IpCidr>());
+        assert!(cidr("10.0.0.0/8").contains(ip("10.9.9.9")));
+        assert!(!cidr("10.0.0.0/8").contains(ip("11.0.0.1")));
+    }
+
+    #[test]
+    fn check_routing_table() {
+        let mut table = IpRoutingTable::new();
+
+        table.insert(cidr("0.0.0.0/0"), "default");
+        table.insert(cidr("10.0.0.0/8"), "corp");
+        table.insert(cidr("10.1.0.0/16"), "lab");
+        table.insert(cidr("10.1.2.0/23"), "rack");
+        table.insert(cidr("2001:db8::/32"), "doc");
+        table.insert(cidr("2001:db8:1::/48"), "doc-1");
+        assert_eq!(6, table.len());
+
+        assert_eq!(Some((cidr("10.1.2.0/23"), &"rack")), table.longest_match(ip("10.1.3.9")));
+        assert_eq!(Some((cidr("10.1.0.0/16"), &"lab")), table.longest_match(ip("10.1.4.9")));
+        assert_eq!(Some((cidr("0.0.0.0/0"), &"default")), table.longest_match(ip("192.168.0.1")));
+        assert_eq!(Some((cidr("2001:db8:1::/48"), &"doc-1")), table.longest_match(ip("2001:db8:1::5")));
+        assert_eq!(None, table.longest_match(ip("fe80::1")));
+
+        let covering = table.covering_prefixes(ip("10.1.2.200")).map(|(c, _)| c.to_string()).collect::<Vec<_>>();
+        assert_eq!(vec!["0.0.0.0/0", "10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/23"], covering);
+
+        let covered = table.covered_by(cidr("10.0.0.0/12")).map(|(_, v)| *v).collect::<Vec<_>>();
+        assert_eq!(vec!["lab", "rack"], covered);
+
+        let covered = table.covered_by(cidr("2001:db8::/16")).map(|(c, _)| c.to_string()).collect::<Vec<_>>();
+        assert_eq!(vec!["2001:db8::/32", "2001:db8:1::/48"], covered);
+
+        assert_eq!(Some("lab"), table.remove(cidr("10.1.0.0/16")));
+        assert_eq!(None, table.remove(cidr("10.1.0.0/16")));
+        assert_eq!(Some((cidr("10.0.0.0/8"), &"corp")), table.longest_match(ip("10.1.4.9")));
+        assert_eq!(Some(&"rack"), table.get(cidr("10.1.2.0/23")));
+        assert_eq!(5, table.iter().count());
+    }
+}
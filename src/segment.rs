@@ -0,0 +1,176 @@
+use crate::node::Node;
+use crate::query::prefixes_of;
+
+// Word segmentation, splitting an unspaced input e.g. "mousepadstore" into a sequence of stored
+// keys. Keys starting at each position are found with a common prefix search from that
+// position, and a dynamic program over positions (from the end backwards) keeps only the
+// keys after which the rest of the input can still be segmented
+
+// A segmentation's keys in input order, each key's text along with its value
+pub type Segmentation<'t, 'a, V> = Vec<(&'t [u8], &'a V)>;
+
+// Lazily enumerates every segmentation, ordered by their key lengths (shorter keys first)
+pub struct Segmentations<'a, 't, V> {
+    input: &'t [u8],
+    edges: Vec<Vec<(usize, &'a V)>>, // keys at each position leading to a complete segmentation
+    path: Vec<(usize, usize)>,       // position and index of the key taken there
+    done: bool,
+}
+
+// Lists the non empty keys starting at each position, keeping only keys after which
+// the remaining input can be segmented
+fn edges<'a, K, V, A>(root: Option<&'a Node<K, V, A>>, input: &[u8]) -> Vec<Vec<(usize, &'a V)>> {
+    let mut edges = vec![vec![]; input.len() + 1];
+    let mut reachable = vec![false; input.len() + 1];
+
+    reachable[input.len()] = true;
+
+    for i in (0..input.len()).rev() {
+        let Some(root) = root else { break };
+
+        edges[i] = prefixes_of(root, &input[i..])
+            .filter(|(len, _)| *len > 0 && reachable[i + len])
+            .collect();
+
+        reachable[i] = !edges[i].is_empty();
+    }
+
+    edges
+}
+
+impl<'a, 't, V> Segmentations<'a, 't, V> {
+    pub(crate) fn new<K, A>(root: Option<&'a Node<K, V, A>>, input: &'t [u8]) -> Self {
+        let edges = edges(root, input);
+        let done = !input.is_empty() && edges[0].is_empty();
+
+        Segmentations {
+            input,
+            edges,
+            path: vec![],
+            done,
+        }
+    }
+
+    // Takes the first key at each position from pos to the end of input
+    fn descend(&mut self, mut pos: usize) {
+        while pos < self.input.len() {
+            self.path.push((pos, 0));
+            pos += self.edges[pos][0].0;
+        }
+    }
+
+    fn segmentation(&self) -> Segmentation<'t, 'a, V> {
+        self.path.iter().map(|&(pos, i)| {
+            let (len, v) = self.edges[pos][i];
+            (&self.input[pos..pos + len], v)
+        }).collect()
+    }
+}
+
+impl<'a, 't, V> Iterator for Segmentations<'a, 't, V> {
+    type Item = Segmentation<'t, 'a, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None
+        }
+
+        if self.path.is_empty() {
+            // First segmentation, or the only one if the input is empty
+            self.descend(0);
+            self.done = self.path.is_empty();
+            return Some(self.segmentation())
+        }
+
+        // Backtrack to the deepest position with an untried key
+        while let Some((pos, i)) = self.path.pop() {
+            if i + 1 < self.edges[pos].len() {
+                self.path.push((pos, i + 1));
+                self.descend(pos + self.edges[pos][i + 1].0);
+                return Some(self.segmentation())
+            }
+        }
+
+        self.done = true;
+        None
+    }
+}
+
+// Finds the segmentation maximizing the sum of its keys' scores, returning it with its total.
+// Ties favour the segmentation found first, i.e. shorter leading keys
+pub(crate) fn best_segmentation<'a, 't, K, V, A, F>(root: Option<&'a Node<K, V, A>>, input: &'t [u8], mut score_fn: F)
+    -> Option<(f64, Segmentation<'t, 'a, V>)>
+where F: FnMut(&[u8], &V) -> f64
+{
+    let edges = edges(root, input);
+
+    // Best total from each position along with the key taken there
+    let mut best: Vec<Option<(f64, usize, Option<&'a V>)>> = vec![None; input.len() + 1];
+    best[input.len()] = Some((0.0, 0, None));
+
+    for i in (0..input.len()).rev() {
+        for &(len, v) in &edges[i] {
+            let Some((rest, _, _)) = best[i + len] else { continue };
+            let total = score_fn(&input[i..i + len], v) + rest;
+
+            if best[i].is_none_or(|(t, _, _)| total > t) {
+                best[i] = Some((total, len, Some(v)));
+            }
+        }
+    }
+
+    let (total, _, _) = best[0]?;
+    let mut segmentation = vec![];
+    let mut pos = 0;
+
+    while let Some((_, len, Some(v))) = best[pos] {
+        segmentation.push((&input[pos..pos + len], v));
+        pos += len;
+    }
+
+    Some((total, segmentation))
+}
+
+// Segmentation unit tests
+
+#[cfg(test)]
+mod tests {
+    use crate::trie::Trie;
+
+    fn words_helper(segmentation: &[(&[u8], &i32)]) -> Vec<String> {
+        segmentation.iter().map(|(k, _)| String::from_utf8(k.to_vec()).unwrap()).collect()
+    }
+
+    fn trie() -> Trie<&'static str, i32> {
+        [("mouse", 5), ("mousepad", 9), ("pad", 4), ("store", 6), ("padstore", 3), ("pads", 2), ("to", 1), ("re", 1), ("m", 1)].iter().cloned().collect()
+    }
+
+    #[test]
+    fn check_segment() {
+        let trie = trie();
+
+        let all = trie.segment("mousepadstore").map(|s| words_helper(&s).join(" ")).collect::<Vec<_>>();
+        assert_eq!(vec!["mouse pad store", "mouse pads to re", "mouse padstore", "mousepad store"], all);
+
+        assert_eq!(0, trie.segment("mousepadstores").count());
+        assert_eq!(1, trie.segment("").count());
+        assert_eq!(vec![vec!["m", "m"]], trie.segment("mm").map(|s| words_helper(&s)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn check_best_segmentation() {
+        let trie = trie();
+
+        let (total, best) = trie.best_segmentation("mousepadstore", |_, v| *v as f64).unwrap();
+        assert_eq!(15.0, total);
+        assert_eq!(vec!["mouse", "pad", "store"], words_helper(&best));
+
+        // Fewest keys, ties favour shorter leading keys
+        let (total, best) = trie.best_segmentation("mousepadstore", |_, _| -1.0).unwrap();
+        assert_eq!(-2.0, total);
+        assert_eq!(vec!["mouse", "padstore"], words_helper(&best));
+
+        assert!(trie.best_segmentation("mousex", |_, _| 1.0).is_none());
+        assert_eq!(Some((0.0, vec![])), trie.best_segmentation("", |_, v| *v as f64));
+    }
+}
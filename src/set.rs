@@ -0,0 +1,201 @@
+use crate::trie::{Trie, TypedKeysIter};
+use crate::node::algebra::Rules;
+use crate::key::TrieKey;
+
+// A set of keys backed by a trie, only key membership is tracked
+// so the values stored are simply unit values
+#[derive(Clone, Debug)]
+pub struct TrieSet<K> {
+    trie: Trie<K, ()>,
+}
+
+impl<K> TrieSet<K>
+{
+    pub fn new() -> Self {
+        TrieSet { trie: Trie::new() }
+    }
+
+    // Adds key to set, returns true if key wasn't already present
+    pub fn insert(&mut self, token: K) -> bool
+    where K: TrieKey
+    {
+        self.trie.insert(token, ()).is_none()
+    }
+
+    pub fn contains(&self, token: K) -> bool
+    where K: TrieKey
+    {
+        self.trie.search(token).is_some()
+    }
+
+    // Removes key from set, returns true if key was present
+    pub fn remove(&mut self, token: K) -> bool
+    where K: TrieKey
+    {
+        self.trie.remove(token).is_some()
+    }
+
+    // Returns all keys which share a common token prefix
+    pub fn all_keys(&self, token: K) -> Option<Vec<Vec<u8>>>
+    where K: TrieKey
+    {
+        self.trie.all_keys(token)
+    }
+
+    // Returns iterator of longest prefix of token that exists in set
+    pub fn longest_prefix(&self, token: K) -> Option<impl Iterator<Item = &'_ u8>>
+    where K: TrieKey
+    {
+        self.trie.longest_prefix(token)
+    }
+
+    // Iterate through all set's keys
+    pub fn iter(&self) -> TypedKeysIter<'_, K, ()>
+    where K: TrieKey
+    {
+        self.trie.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.trie.clear()
+    }
+}
+
+// Set algebra, each operation walks both sets' tries in lockstep
+
+impl<K: Clone> TrieSet<K>
+{
+    // Keys in either set
+    pub fn union(&self, other: &TrieSet<K>) -> TrieSet<K> {
+        self.combine(other, Rules::new(true, true, |_, _| Some(())))
+    }
+
+    // Keys in both sets
+    pub fn intersection(&self, other: &TrieSet<K>) -> TrieSet<K> {
+        self.combine(other, Rules::new(false, false, |_, _| Some(())))
+    }
+
+    // Keys in this set but not in the other set
+    pub fn difference(&self, other: &TrieSet<K>) -> TrieSet<K> {
+        self.combine(other, Rules::new(true, false, |_, _| None))
+    }
+
+    // Keys in exactly one of the two sets
+    pub fn symmetric_difference(&self, other: &TrieSet<K>) -> TrieSet<K> {
+        self.combine(other, Rules::new(true, true, |_, _| None))
+    }
+
+    fn combine<F>(&self, other: &TrieSet<K>, rules: Rules<F>) -> TrieSet<K>
+    where F: FnMut((), ()) -> Option<()>
+    {
+        TrieSet { trie: self.trie.combine_ref(&other.trie, rules) }
+    }
+}
+
+impl<K> Default for TrieSet<K> {
+    fn default() -> TrieSet<K> {
+        TrieSet::new()
+    }
+}
+
+impl<K> FromIterator<K> for TrieSet<K>
+where K: TrieKey
+{
+    fn from_iter<I>(iter: I) -> TrieSet<K>
+    where
+        I: IntoIterator<Item = K>,
+    {
+        let mut set = TrieSet::new();
+
+        for key in iter {
+            set.insert(key);
+        }
+
+        set
+    }
+}
+
+// TrieSet unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn keys_helper(set: &TrieSet<&str>) -> BTreeSet<String> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn check_membership() {
+        let mut set: TrieSet<_> = ["anthem", "anti", "anthemion", "and"].into_iter().collect();
+
+        assert_eq!(4, set.len());
+        assert!(set.contains("anthem"));
+        assert!(!set.contains("ant"));
+        assert!(!set.insert("anti"));
+        assert!(set.insert("ant"));
+        assert!(set.remove("anthem"));
+        assert!(!set.remove("anthem"));
+        assert_eq!(BTreeSet::from(["and", "ant", "anthemion", "anti"].map(String::from)), keys_helper(&set));
+    }
+
+    #[test]
+    fn check_set_algebra() {
+        // Labels diverge at different points in each trie e.g. "anthem" vs "ant" + "hem"
+        let a: TrieSet<_> = ["anthem", "anti", "and", "mouse"].into_iter().collect();
+        let b: TrieSet<_> = ["ant", "anthemion", "anti", "mousepad", "mouse"].into_iter().collect();
+
+        let union = a.union(&b);
+        assert_eq!(7, union.len());
+        assert_eq!(BTreeSet::from(["and", "ant", "anthem", "anthemion", "anti", "mouse", "mousepad"].map(String::from)), keys_helper(&union));
+
+        let intersection = a.intersection(&b);
+        assert_eq!(BTreeSet::from(["anti", "mouse"].map(String::from)), keys_helper(&intersection));
+        assert!(intersection.contains("mouse"));
+        assert!(!intersection.contains("mousepad"));
+
+        let difference = a.difference(&b);
+        assert_eq!(BTreeSet::from(["and", "anthem"].map(String::from)), keys_helper(&difference));
+
+        let sym = a.symmetric_difference(&b);
+        assert_eq!(BTreeSet::from(["and", "ant", "anthem", "anthemion", "mousepad"].map(String::from)), keys_helper(&sym));
+
+        // Labels are compressed again once combined
+        let labels = intersection.trie.labels().collect::<BTreeSet<&[u8]>>();
+        assert_eq!(BTreeSet::from(["anti".as_bytes(), "mouse".as_bytes()]), labels);
+
+        assert!(a.intersection(&TrieSet::new()).is_empty());
+    }
+
+    #[test]
+    fn check_set_algebra_operands() {
+        // Keys over a small alphabet so the two tries' labels overlap and diverge a lot
+        let words = |seed: usize| (0..200).map(move |i| {
+            let n = (i * 7919 + seed) % 1000;
+            format!("{:b}", n).replace('1', "ab")
+        }).collect::<BTreeSet<String>>();
+
+        let (left, right) = (words(3), words(11));
+        let a: TrieSet<&str> = left.iter().map(String::as_str).collect();
+        let b: TrieSet<&str> = right.iter().map(String::as_str).collect();
+
+        assert_eq!(left.union(&right).cloned().collect::<BTreeSet<_>>(), keys_helper(&a.union(&b)));
+        assert_eq!(left.intersection(&right).cloned().collect::<BTreeSet<_>>(), keys_helper(&a.intersection(&b)));
+        assert_eq!(left.difference(&right).cloned().collect::<BTreeSet<_>>(), keys_helper(&a.difference(&b)));
+        assert_eq!(left.symmetric_difference(&right).cloned().collect::<BTreeSet<_>>(), keys_helper(&a.symmetric_difference(&b)));
+
+        // Both operands are left as they were
+        assert_eq!(left, keys_helper(&a));
+        assert_eq!(right, keys_helper(&b));
+        assert_eq!(a.union(&b).len(), a.union(&b).iter().count());
+    }
+}
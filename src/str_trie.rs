@@ -0,0 +1,266 @@
+use std::mem;
+
+use crate::trie::{Trie, Subtrie, TypedKeysIter, TypedPairsIter};
+use crate::query::{longest_prefix_match, prefixes_of, all_keys, count_prefix};
+use crate::normalize::{Normalizer, Identity};
+
+// A trie keyed by strings, labels are split on bytes so a multi-byte char may straddle
+// two labels, hence queries here only ever report prefixes ending on char boundaries
+// and keys are returned as &str / String rather than raw bytes
+
+#[derive(Clone, Debug)]
+pub struct StrTrie<V> {
+    trie: Trie<String, V>,
+}
+
+impl<V> StrTrie<V> {
+    pub fn new() -> Self {
+        StrTrie { trie: Trie::new() }
+    }
+
+    pub fn insert(&mut self, token: &str, value: V) -> Option<V> {
+        self.trie.insert_bytes(token.as_bytes().into(), value)
+    }
+
+    pub fn search(&self, token: &str) -> Option<&'_ V> {
+        self.trie.root().and_then(|n| n.search(token.as_bytes()))
+    }
+
+    pub fn remove(&mut self, token: &str) -> Option<V> {
+        self.trie.remove_bytes(token.as_bytes())
+    }
+
+    // Returns the longest key that is a prefix of token, as a slice of token
+    pub fn longest_prefix<'t>(&self, token: &'t str) -> Option<&'t str> {
+        self.longest_prefix_match(token).map(|(prefix, _)| prefix)
+    }
+
+    // Returns the longest key that is a prefix of token along with its value
+    pub fn longest_prefix_match<'t>(&self, token: &'t str) -> Option<(&'t str, &'_ V)> {
+        let (len, v) = self.trie.root().and_then(|n| longest_prefix_match(n, token.as_bytes()))?;
+
+        // Keys are whole strings so a matched key always ends on a char boundary
+        Some((&token[..len], v))
+    }
+
+    // Iterate through all keys which are prefixes of token, shortest first
+    pub fn prefixes_of<'t>(&self, token: &'t str) -> impl Iterator<Item = (&'t str, &'_ V)> {
+        self.trie.root().map(|n| prefixes_of(n, token.as_bytes()))
+            .into_iter().flatten()
+            .map(move |(len, v)| (&token[..len], v))
+    }
+
+    // Returns the longest run of token's leading chars that some key starts with. Unlike the
+    // byte level path, a char whose bytes only partially match is excluded
+    pub fn common_prefix<'t>(&self, token: &'t str) -> &'t str {
+        let Some(root) = self.trie.root() else { return "" };
+
+        // Char end offsets, a prefix being present implies all shorter prefixes are
+        let ends = token.char_indices().map(|(i, c)| i + c.len_utf8()).collect::<Vec<_>>();
+        let count = ends.partition_point(|&end| count_prefix(root, &token.as_bytes()[..end]) > 0);
+
+        &token[..count.checked_sub(1).map_or(0, |i| ends[i])]
+    }
+
+    // Returns all keys which start with prefix
+    pub fn all_keys(&self, prefix: &str) -> Vec<String> {
+        self.trie.root()
+            .and_then(|n| all_keys(n, prefix.as_bytes()))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|k| String::from_utf8(k).ok())
+            .collect()
+    }
+
+    // Iterate through all keys in order
+    pub fn keys(&self) -> TypedKeysIter<'_, String, V> {
+        self.trie.keys()
+    }
+
+    // Iterate through all keys and values in key order
+    pub fn iter(&self) -> TypedPairsIter<'_, String, V> {
+        self.trie.pairs()
+    }
+
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.trie.clear()
+    }
+}
+
+impl<V> Default for StrTrie<V> {
+    fn default() -> Self {
+        StrTrie::new()
+    }
+}
+
+impl<S: AsRef<str>, V> FromIterator<(S, V)> for StrTrie<V> {
+    fn from_iter<I: IntoIterator<Item = (S, V)>>(iter: I) -> Self {
+        let mut trie = StrTrie::new();
+
+        for (k, v) in iter {
+            trie.insert(k.as_ref(), v);
+        }
+
+        trie
+    }
+}
+
+// A string trie which normalizes keys before storing or looking them up, so that equivalent
+// spellings e.g. "MOU" and "mou" address the same entry. Each entry keeps the display form
+// it was first inserted with, which is what results report
+
+#[derive(Clone, Debug)]
+pub struct NormalizedTrie<V, N = Identity> {
+    trie: StrTrie<(String, V)>,
+    normalizer: N,
+}
+
+impl<V, N: Normalizer + Default> NormalizedTrie<V, N> {
+    pub fn new() -> Self {
+        NormalizedTrie::with_normalizer(N::default())
+    }
+}
+
+impl<V, N: Normalizer> NormalizedTrie<V, N> {
+    pub fn with_normalizer(normalizer: N) -> Self {
+        NormalizedTrie {
+            trie: StrTrie::new(),
+            normalizer,
+        }
+    }
+
+    pub fn normalizer(&self) -> &N {
+        &self.normalizer
+    }
+
+    // Inserts value, an existing entry for an equivalent key keeps its display form
+    pub fn insert(&mut self, token: &str, value: V) -> Option<V> {
+        let key = self.normalizer.normalize(token);
+        let mut value = Some(value);
+        let (_, stored) = self.trie.trie.get_or_insert_with_bytes(key.as_bytes(), || (token.to_string(), value.take().unwrap()));
+
+        value.map(|v| mem::replace(stored, v))
+    }
+
+    pub fn search(&self, token: &str) -> Option<&'_ V> {
+        self.get_key_value(token).map(|(_, v)| v)
+    }
+
+    // Retrieves the stored display form of an equivalent key along with its value
+    pub fn get_key_value(&self, token: &str) -> Option<(&'_ str, &'_ V)> {
+        self.trie.search(&self.normalizer.normalize(token)).map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn remove(&mut self, token: &str) -> Option<V> {
+        self.trie.remove(&self.normalizer.normalize(token)).map(|(_, v)| v)
+    }
+
+    // Returns display form of the longest key whose normalized form prefixes token's
+    pub fn longest_prefix(&self, token: &str) -> Option<&'_ str> {
+        self.longest_prefix_match(token).map(|(k, _)| k)
+    }
+
+    pub fn longest_prefix_match(&self, token: &str) -> Option<(&'_ str, &'_ V)> {
+        self.trie.longest_prefix_match(&self.normalizer.normalize(token)).map(|(_, (k, v))| (k.as_str(), v))
+    }
+
+    // Returns display forms of all keys starting with prefix, in normalized key order
+    pub fn all_keys(&self, prefix: &str) -> Vec<&'_ str> {
+        let prefix = self.normalizer.normalize(prefix);
+
+        Subtrie::new(&self.trie.trie, prefix.as_bytes().to_vec()).iter_bytes()
+            .map(|(_, (k, _))| k.as_str())
+            .collect()
+    }
+
+    // Iterate through display keys and values, in normalized key order
+    pub fn iter(&self) -> impl Iterator<Item = (&'_ str, &'_ V)> {
+        self.trie.trie.values().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.trie.clear()
+    }
+}
+
+impl<V, N: Normalizer + Default> Default for NormalizedTrie<V, N> {
+    fn default() -> Self {
+        NormalizedTrie::new()
+    }
+}
+
+// StrTrie unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_char_boundaries() {
+        // "é" is 0xC3 0xA9 and "ê" is 0xC3 0xAA, so the labels fork mid char
+        let trie: StrTrie<_> = [("café", 1), ("cafê", 2), ("caf", 3), ("日本", 4), ("日本語", 5)].into_iter().collect();
+
+        assert_eq!("caf", trie.common_prefix("cafè"));
+        assert_eq!("café", trie.common_prefix("cafés"));
+        assert_eq!("日本", trie.common_prefix("日本人"));
+        assert_eq!("", trie.common_prefix("月"));
+
+        assert_eq!(Some("caf"), trie.longest_prefix("cafè"));
+        assert_eq!(Some(("日本語", &5)), trie.longest_prefix_match("日本語です"));
+        assert_eq!(vec![("日本", &4), ("日本語", &5)], trie.prefixes_of("日本語").collect::<Vec<_>>());
+        assert_eq!(None, trie.longest_prefix("ca"));
+
+        assert_eq!(vec!["caf", "café", "cafê", "日本", "日本語"], trie.keys().collect::<Vec<_>>());
+        assert_eq!(vec!["日本", "日本語"], { let mut k = trie.all_keys("日"); k.sort(); k });
+
+        let mut trie = trie;
+        assert_eq!(Some(1), trie.remove("café"));
+        assert_eq!(Some(&2), trie.search("cafê"));
+        assert_eq!("caf", trie.common_prefix("café"));
+        assert_eq!(4, trie.len());
+    }
+
+    #[test]
+    fn check_normalized_trie() {
+        use crate::normalize::{CaseFold, Nfc};
+
+        let mut trie: NormalizedTrie<_, (CaseFold, Nfc)> = NormalizedTrie::new();
+
+        trie.insert("Mousepad", 1);
+        trie.insert("mousy", 2);
+        trie.insert("Café", 3);
+        trie.insert("moneypot", 4);
+
+        for typed in ["Mou", "MOU", "mou"] {
+            assert_eq!(vec!["Mousepad", "mousy"], trie.all_keys(typed));
+        }
+
+        // combining accent matches the precomposed form
+        assert_eq!(Some(("Café", &3)), trie.get_key_value("cafe\u{301}"));
+        assert_eq!(Some("Café"), trie.longest_prefix("CAFE\u{301} au lait"));
+
+        // equivalent key replaces the value but keeps the first display form
+        assert_eq!(Some(1), trie.insert("MOUSEPAD", 10));
+        assert_eq!(Some(("Mousepad", &10)), trie.get_key_value("mousepad"));
+        assert_eq!(4, trie.len());
+
+        assert_eq!(Some(2), trie.remove("MOUSY"));
+        assert_eq!(vec![("Café", &3), ("moneypot", &4), ("Mousepad", &10)], trie.iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,247 @@
+
+use crate::node::KeyedNode;
+use crate::aggregate::TrieAggregate;
+use crate::key::TrieKey;
+use crate::trie::{Trie, PairsIter};
+use crate::query::{longest_prefix, all_keys};
+use crate::traverse::{TraverseType, TraverseResult, traverse};
+
+// Subtrie views scope a trie to the keys starting with a given prefix without copying,
+// keys passed to and returned from a view are relative to the view's prefix. A relative key
+// needn't decode as the trie's key type (e.g. the tail of an integer), hence the view hands
+// out keys as raw bytes, the methods doing so are named accordingly
+
+#[derive(Debug)]
+pub struct Subtrie<'a, K, V, A = ()> {
+    trie: &'a Trie<K, V, A>,
+    prefix: Vec<u8>,
+}
+
+// Mutable view, inserts and removes are applied to the underlying trie
+// so that its size and compressed labels remain accurate
+#[derive(Debug)]
+pub struct SubtrieMut<'a, K, V, A = ()> {
+    trie: &'a mut Trie<K, V, A>,
+    prefix: Vec<u8>,
+}
+
+// Joins a view's prefix and a relative key into the key stored in the trie
+fn full_key(prefix: &[u8], token: &[u8]) -> Vec<u8> {
+    let mut full = Vec::with_capacity(prefix.len() + token.len());
+    full.extend_from_slice(prefix);
+    full.extend_from_slice(token);
+    full
+}
+
+impl<'a, K, V, A> Subtrie<'a, K, V, A> {
+    pub(crate) fn new(trie: &'a Trie<K, V, A>, prefix: Vec<u8>) -> Self {
+        Subtrie {
+            trie,
+            prefix,
+        }
+    }
+
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    // Retrieves relative key's stored value
+    pub fn search(&self, token: K) -> Option<&'a V>
+    where K: TrieKey
+    {
+        let full = self.full_key(&token.encode());
+        self.trie.root().and_then(|n| n.search(&full))
+    }
+
+    // Returns iterator of longest prefix of relative token that exists in view
+    pub fn longest_prefix_bytes(&self, token: K) -> Option<impl Iterator<Item = &'a u8>>
+    where K: TrieKey
+    {
+        let full = self.full_key(&token.encode());
+        let result = self.trie.root().and_then(|n| longest_prefix(n, &full))?.collect::<Vec<&'a u8>>();
+
+        // A longest prefix shorter than the view's prefix lies outside the view
+        if result.len() < self.prefix.len() {
+            return None
+        }
+
+        Some(result.into_iter().skip(self.prefix.len()))
+    }
+
+    // Returns all relative keys which share a common relative token prefix
+    pub fn all_key_bytes(&self, token: K) -> Option<Vec<Vec<u8>>>
+    where K: TrieKey
+    {
+        let full = self.full_key(&token.encode());
+        let keys = self.trie.root().and_then(|n| all_keys(n, &full))?;
+
+        Some(keys.into_iter().map(|k| k[self.prefix.len()..].to_vec()).collect())
+    }
+
+    // Iterate through view's relative keys and values
+    pub fn iter_bytes(&self) -> PairsIter<'a, K, V, A> {
+        self.anchor().map_or_else(
+            PairsIter::default, |(n, bytes)| PairsIter::with_key(n, bytes, n.key_count())
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.anchor().map_or(0, |(n, _)| n.key_count())
+    }
+
+    // Returns the combined summary of the values of all relative keys starting with token,
+    // None while the trie's summaries await a write-back, see Trie::aggregate_prefix
+    pub fn aggregate_prefix(&self, token: K) -> Option<A>
+    where K: TrieKey, A: TrieAggregate<V> + Clone
+    {
+        self.trie.aggregate_prefix_bytes(&self.full_key(&token.encode()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn full_key(&self, token: &[u8]) -> Vec<u8> {
+        full_key(&self.prefix, token)
+    }
+
+    // Finds the node where the view's prefix ends, along with the node's relative key.
+    // If the prefix ends in the middle of a label, the node's relative key is simply
+    // the label's remaining suffix
+    fn anchor(&self) -> Option<KeyedNode<'a, K, V, A>> {
+        let root = self.trie.root()?;
+
+        if self.prefix.is_empty() {
+            return Some((root, vec![]))
+        }
+
+        match traverse(root, &self.prefix, TraverseType::Search)? {
+            TraverseResult::Terminal(_, n) => Some((n, vec![])),
+            TraverseResult::PartialTerminal(_, n, extra) => Some((n, extra.to_vec())),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<'a, K, V, A: TrieAggregate<V>> SubtrieMut<'a, K, V, A> {
+    pub(crate) fn new(trie: &'a mut Trie<K, V, A>, prefix: Vec<u8>) -> Self {
+        SubtrieMut {
+            trie,
+            prefix,
+        }
+    }
+
+    // Reborrows the mutable view as a read only view
+    pub fn view(&self) -> Subtrie<'_, K, V, A> {
+        Subtrie::new(self.trie, self.prefix.clone())
+    }
+
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    pub fn search(&self, token: K) -> Option<&'_ V>
+    where K: TrieKey
+    {
+        let full = self.full_key(&token.encode());
+        self.trie.root().and_then(|n| n.search(&full))
+    }
+
+    // Returns iterator of longest prefix of relative token that exists in view
+    pub fn longest_prefix_bytes(&self, token: K) -> Option<impl Iterator<Item = &'_ u8>>
+    where K: TrieKey
+    {
+        self.view().longest_prefix_bytes(token)
+    }
+
+    pub fn all_key_bytes(&self, token: K) -> Option<Vec<Vec<u8>>>
+    where K: TrieKey
+    {
+        self.view().all_key_bytes(token)
+    }
+
+    pub fn iter_bytes(&self) -> PairsIter<'_, K, V, A> {
+        self.view().iter_bytes()
+    }
+
+    // Inserts value using the relative key
+    pub fn insert(&mut self, token: K, value: V) -> Option<V>
+    where K: TrieKey
+    {
+        let full = self.full_key(&token.encode());
+        self.trie.insert_bytes(full.into(), value)
+    }
+
+    // Removes relative key's value
+    pub fn remove(&mut self, token: K) -> Option<V>
+    where K: TrieKey
+    {
+        let full = self.full_key(&token.encode());
+        self.trie.remove_bytes(&full)
+    }
+
+    pub fn len(&self) -> usize {
+        self.view().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn full_key(&self, token: &[u8]) -> Vec<u8> {
+        full_key(&self.prefix, token)
+    }
+}
+
+// Subtrie unit tests
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use crate::trie::Trie;
+
+    fn pairs_helper<'a>(pairs: impl Iterator<Item = (Vec<u8>, &'a i32)>) -> BTreeSet<(String, i32)> {
+        pairs.map(|(k, v)| (String::from_utf8(k).unwrap(), *v)).collect()
+    }
+
+    #[test]
+    fn check_subtrie_view() {
+        let trie: Trie<_, _> = [("/api/v2/users", 1), ("/api/v2/items", 2), ("/api/v1/users", 3), ("/api/v2", 4)].iter().cloned().collect();
+
+        let view = trie.subtrie("/api/v2/");
+        assert_eq!(2, view.len());
+        assert_eq!(&1, view.search("users").unwrap());
+        assert_eq!(None, view.search("/api/v1/users"));
+        assert_eq!(pairs_helper(view.iter_bytes()), BTreeSet::from([("users".to_string(), 1), ("items".to_string(), 2)]));
+        assert_eq!(vec![b"items".to_vec()], view.all_key_bytes("it").unwrap());
+
+        // "/api/v2" is shorter than the view's prefix
+        assert!(view.longest_prefix_bytes("x").is_none());
+        let prefix = view.longest_prefix_bytes("users/42").unwrap().cloned().collect::<Vec<u8>>();
+        assert_eq!(b"users".to_vec(), prefix);
+
+        // prefix ends in the middle of the "sers" label
+        let view = trie.subtrie("/api/v1/u");
+        assert_eq!(pairs_helper(view.iter_bytes()), BTreeSet::from([("sers".to_string(), 3)]));
+        assert!(trie.subtrie("/api/v3").is_empty());
+    }
+
+    #[test]
+    fn check_subtrie_mut() {
+        let mut trie: Trie<_, _> = [("/api/v2/users", 1), ("/api/v1/users", 3)].iter().cloned().collect();
+
+        let mut view = trie.subtrie_mut("/api/v2/");
+        assert_eq!(None, view.insert("items", 2));
+        assert_eq!(Some(1), view.remove("users"));
+        assert_eq!(&2, view.search("items").unwrap());
+        assert_eq!(1, view.len());
+
+        let prefix = view.longest_prefix_bytes("itemsx").unwrap().cloned().collect::<Vec<u8>>();
+        assert_eq!(b"items".to_vec(), prefix);
+        assert!(view.longest_prefix_bytes("x").is_none());
+
+        assert_eq!(2, trie.len());
+        assert_eq!(&2, trie.search("/api/v2/items").unwrap());
+        assert_eq!(None, trie.search("/api/v2/users"));
+    }
+}
@@ -0,0 +1,268 @@
+use std::io::{self, Read};
+
+use crate::node::Node;
+use crate::query::longest_prefix_walk;
+
+// Greedy longest match tokenization, at each position the longest key prefixing the
+// remaining input becomes the next token. Runs of bytes at which no key matches are
+// grouped into a single unmatched token. Each step is a single walk down the trie
+// without a traversal stack, so tokenizing allocates nothing per token
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Token<T, V> {
+    Key(T, V),    // matched key text along with its value
+    Unmatched(T), // run of bytes no key matches
+}
+
+impl<T, V> Token<T, V> {
+    pub fn text(&self) -> &T {
+        match self {
+            Token::Key(t, _) | Token::Unmatched(t) => t,
+        }
+    }
+
+    pub fn value(&self) -> Option<&V> {
+        match self {
+            Token::Key(_, v) => Some(v),
+            Token::Unmatched(_) => None,
+        }
+    }
+}
+
+// Tokenizes an in memory input
+pub struct Tokens<'a, 't, K, V, A = ()> {
+    root: Option<&'a Node<K, V, A>>,
+    input: &'t [u8],
+    pos: usize,
+}
+
+// Tokenizes a reader's bytes, buffering only as much lookahead as the longest match needs.
+// Unmatched runs are split into tokens of at most CHUNK bytes, so a long stream no key
+// matches isn't buffered whole
+pub struct ReaderTokens<'a, R, K, V, A = ()> {
+    root: Option<&'a Node<K, V, A>>,
+    reader: R,
+    buf: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+const CHUNK: usize = 8 * 1024;
+
+// Length and value of the longest non empty key prefixing bytes
+#[inline]
+fn longest_key<'a, K, V, A>(root: Option<&'a Node<K, V, A>>, bytes: &[u8]) -> (Option<(usize, &'a V)>, bool) {
+    match root {
+        Some(root) => {
+            let (result, more) = longest_prefix_walk(root, bytes);
+            (result.filter(|(len, _)| *len > 0), more)
+        },
+        None => (None, false),
+    }
+}
+
+impl<'a, 't, K, V, A> Tokens<'a, 't, K, V, A> {
+    pub(crate) fn new(root: Option<&'a Node<K, V, A>>, input: &'t [u8]) -> Self {
+        Tokens { root, input, pos: 0 }
+    }
+
+    // Offset of the next token within the input
+    pub fn offset(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<'a, 't, K, V, A> Iterator for Tokens<'a, 't, K, V, A> {
+    type Item = Token<&'t [u8], &'a V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        let mut end = start;
+
+        while end < self.input.len() {
+            if let (Some((len, v)), _) = longest_key(self.root, &self.input[end..]) {
+                if end > start {
+                    break
+                }
+
+                self.pos = start + len;
+                return Some(Token::Key(&self.input[start..self.pos], v))
+            }
+
+            end += 1;
+        }
+
+        self.pos = end;
+        (end > start).then(|| Token::Unmatched(&self.input[start..end]))
+    }
+}
+
+impl<'a, R: Read, K, V, A> ReaderTokens<'a, R, K, V, A> {
+    pub(crate) fn new(root: Option<&'a Node<K, V, A>>, reader: R) -> Self {
+        ReaderTokens {
+            root,
+            reader,
+            buf: Vec::new(),
+            pos: 0,
+            eof: false,
+        }
+    }
+
+    // Appends the next chunk of input to the buffer, flagging the end of input
+    fn fill(&mut self) -> io::Result<()> {
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK, 0);
+
+        let read = loop {
+            match self.reader.read(&mut self.buf[len..]) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+
+        self.buf.truncate(len + *read.as_ref().unwrap_or(&0));
+        self.eof = matches!(read, Ok(0));
+        read.map(|_| ())
+    }
+
+    fn next_token(&mut self) -> io::Result<Option<Token<Vec<u8>, &'a V>>> {
+        // Drop consumed input once it makes up most of the buffer
+        if self.pos * 2 >= self.buf.len() {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+
+        let start = self.pos;
+        let mut end = start;
+
+        loop {
+            if end - start == CHUNK {
+                break
+            }
+
+            if end == self.buf.len() {
+                if self.eof {
+                    break
+                }
+
+                self.fill()?;
+                continue
+            }
+
+            let (result, more) = longest_key(self.root, &self.buf[end..]);
+
+            // A longer match may follow in input not yet read
+            if more && !self.eof {
+                self.fill()?;
+                continue
+            }
+
+            if let Some((len, v)) = result {
+                if end > start {
+                    break
+                }
+
+                self.pos = start + len;
+                return Ok(Some(Token::Key(self.buf[start..self.pos].to_vec(), v)))
+            }
+
+            end += 1;
+        }
+
+        self.pos = end;
+        Ok((end > start).then(|| Token::Unmatched(self.buf[start..end].to_vec())))
+    }
+}
+
+impl<'a, R: Read, K, V, A> Iterator for ReaderTokens<'a, R, K, V, A> {
+    type Item = io::Result<Token<Vec<u8>, &'a V>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_token().transpose()
+    }
+}
+
+// Tokenizer unit tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::Trie;
+
+    fn trie() -> Trie<&'static str, i32> {
+        [("un", 1), ("believ", 2), ("able", 3), ("a", 4), ("ab", 5), ("be", 6), ("lie", 7), ("unbelievable", 8)].iter().cloned().collect()
+    }
+
+    fn text_helper<T: AsRef<[u8]>, V>(tokens: impl Iterator<Item = Token<T, V>>) -> Vec<String> {
+        tokens.map(|t| match t {
+            Token::Key(k, _) => String::from_utf8(k.as_ref().to_vec()).unwrap(),
+            Token::Unmatched(u) => format!("?{}", String::from_utf8(u.as_ref().to_vec()).unwrap()),
+        }).collect()
+    }
+
+    #[test]
+    fn check_tokenize() {
+        let trie = trie();
+
+        assert_eq!(vec!["unbelievable", "?s"], text_helper(trie.tokenize("unbelievables")));
+        assert_eq!(vec!["un", "believ", "ab", "?xx", "lie", "?!"], text_helper(trie.tokenize("unbelievabxxlie!")));
+        assert_eq!(vec!["?xyz"], text_helper(trie.tokenize("xyz")));
+        assert!(trie.tokenize("").next().is_none());
+
+        let mut tokens = trie.tokenize("ablex");
+        assert_eq!(Some(Token::Key(&b"able"[..], &3)), tokens.next());
+        assert_eq!(4, tokens.offset());
+        assert_eq!(Some(&4), Token::<&[u8], _>::Key(&b"a"[..], 4).value());
+    }
+
+    // Reader yielding a single byte per read, so every match spans several fills
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.0.len().min(1).min(buf.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn check_tokenize_reader() {
+        let trie = trie();
+
+        for input in ["unbelievables", "unbelievabxxlie!", "xyz", "", "abab"] {
+            let expected = text_helper(trie.tokenize(input));
+            let tokens = trie.tokenize_reader(Trickle(input.as_bytes())).collect::<io::Result<Vec<_>>>().unwrap();
+            assert_eq!(expected, text_helper(tokens.into_iter()));
+
+            let tokens = trie.tokenize_reader(input.as_bytes()).collect::<io::Result<Vec<_>>>().unwrap();
+            assert_eq!(expected, text_helper(tokens.into_iter()));
+        }
+    }
+
+    #[test]
+    fn check_tokenize_reader_unmatched_run() {
+        let trie = trie();
+        let input = io::repeat(b'x').take(5 * CHUNK as u64 + 10).chain(&b"able"[..]);
+        let mut tokens = trie.tokenize_reader(input);
+        let mut unmatched = 0;
+
+        while let Some(token) = tokens.next().transpose().unwrap() {
+            assert!(tokens.buf.len() <= 2 * CHUNK);
+
+            match token {
+                Token::Unmatched(run) => {
+                    assert!(run.len() <= CHUNK && run.iter().all(|b| *b == b'x'));
+                    unmatched += run.len();
+                },
+                Token::Key(key, _) => {
+                    assert_eq!(b"able".to_vec(), key);
+                    assert!(tokens.next().is_none());
+                },
+            }
+        }
+
+        assert_eq!(5 * CHUNK + 10, unmatched);
+    }
+}
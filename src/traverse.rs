@@ -1,6 +1,6 @@
 use crate::node::Node;
 
-pub(crate) type TraverseStack<'a, K, V> = Vec<TraverseItem<'a, K, V>>;
+pub(crate) type TraverseStack<'a, K, V, A> = Vec<TraverseItem<'a, K, V, A>>;
 
 #[derive(Debug, Copy, Clone)]
 pub(crate) enum TraverseType {
@@ -11,8 +11,8 @@ pub(crate) enum TraverseType {
 
 // KeyMatch represents the match state after a token match with an interior label
 #[derive(Debug)]
-pub(crate) struct KeyMatch<'a, 'b, K: 'a, V: 'a> {
-    pub(crate) next: &'a Node<K, V>,
+pub(crate) struct KeyMatch<'a, 'b, K: 'a, V: 'a, A: 'a> {
+    pub(crate) next: &'a Node<K, V, A>,
     pub(crate) common: &'a [u8],
     pub(crate) leftover: SuffixType<'a, 'b>,
     pub(crate) edge_key: u8,
@@ -29,8 +29,8 @@ pub(crate) enum SuffixType<'a, 'b> {
 
 // Defines Stack item struct type
 #[derive(Debug)]
-pub(crate) struct TraverseItem<'a, K: 'a, V: 'a>{
-    pub(crate) node: &'a Node<K, V>,
+pub(crate) struct TraverseItem<'a, K: 'a, V: 'a, A: 'a>{
+    pub(crate) node: &'a Node<K, V, A>,
     pub(crate) next_key: u8,
     pub(crate) label: Option<&'a [u8]>,
     pub(crate) level: u32,
@@ -38,14 +38,14 @@ pub(crate) struct TraverseItem<'a, K: 'a, V: 'a>{
 
 #[derive(Debug)]
 #[allow(dead_code)]
-pub(crate) enum TraverseResult<'a, K: 'a, V: 'a> {
-    Stack(Vec<TraverseItem<'a, K, V>>),
-    PartialTerminal(bool, &'a Node<K, V>, &'a [u8]), // If match prefix matches some of the terminal's label
-    Terminal(bool, &'a Node<K, V>),
+pub(crate) enum TraverseResult<'a, K: 'a, V: 'a, A: 'a> {
+    Stack(Vec<TraverseItem<'a, K, V, A>>),
+    PartialTerminal(bool, &'a Node<K, V, A>, &'a [u8]), // If match prefix matches some of the terminal's label
+    Terminal(bool, &'a Node<K, V, A>),
 }
 
-impl<'a, 'b, K: 'a, V: 'a> KeyMatch<'a, 'b, K, V> {
-    pub fn new(next: &'a Node<K, V>, common: &'a [u8], leftover: SuffixType<'a, 'b>, edge_key: u8) -> Self {
+impl<'a, 'b, K: 'a, V: 'a, A: 'a> KeyMatch<'a, 'b, K, V, A> {
+    pub fn new(next: &'a Node<K, V, A>, common: &'a [u8], leftover: SuffixType<'a, 'b>, edge_key: u8) -> Self {
         KeyMatch {
             next,
             common,
@@ -68,9 +68,9 @@ impl<'a, 'b> SuffixType<'a, 'b> {
 }
 
 // Matches token and relevant interior label
-pub(crate) fn traverse_match<'a, 'b, K, V>(node: &'a Node<K, V>, token: &'b [u8]) -> Option<KeyMatch<'a, 'b, K, V>> {
+pub(crate) fn traverse_match<'a, 'b, K, V, A>(node: &'a Node<K, V, A>, token: &'b [u8]) -> Option<KeyMatch<'a, 'b, K, V, A>> {
     let mut index = 0;
-    let next_node: &Node<K, V>;
+    let next_node: &Node<K, V, A>;
     let edge_key = token[0];
 
     if let Some(box_ref) = node.lookup_edge(edge_key) {
@@ -103,12 +103,22 @@ pub(crate) fn traverse_match<'a, 'b, K, V>(node: &'a Node<K, V>, token: &'b [u8]
 }
 
 // Iterates through trie matching interior labels, accumulating a result
-pub(crate) fn traverse<'a, 'b, K, V>(node: &'a Node<K, V>, token: &'b [u8], traverse_type: TraverseType) -> Option<TraverseResult<'a, K, V>> {
-    let mut stack: TraverseStack<K, V> = Vec::new();
-    let mut current: &Node<K, V> = node;
+pub(crate) fn traverse<'a, K, V, A>(node: &'a Node<K, V, A>, token: &[u8], traverse_type: TraverseType) -> Option<TraverseResult<'a, K, V, A>> {
+    let mut stack: TraverseStack<K, V, A> = Vec::new();
+    let mut current: &Node<K, V, A> = node;
     let mut level: u32 = 0;
     let mut partial_terminal = None;
 
+    // An empty token matches the start node itself
+    if token.is_empty() {
+        let value = match traverse_type {
+            TraverseType::Search => TraverseResult::Terminal(node.is_key(), node),
+            _ => TraverseResult::Stack(vec![TraverseItem{node, next_key: Default::default(), label: None, level}]),
+        };
+
+        return Some(value)
+    }
+
     // Seed node stack with root node
     // Populate the stack by iterating through token byte chunks at each matching node level
     let mut nav_token = token;
@@ -151,8 +161,14 @@ pub(crate) fn traverse<'a, 'b, K, V>(node: &'a Node<K, V>, token: &'b [u8], trav
                     TraverseType::Fold => return None,
                 }
             },
-            // These KeyMatch types indicate the prefix token is not found (completely or even partially) in the trie yet
-            Some(_) => return None,
+            // These KeyMatch types indicate the prefix token is not found (completely or even partially) in the trie yet,
+            // although for a partial fold the antecedent nodes already on the stack still form a valid fold
+            Some(_) => {
+                match traverse_type {
+                    TraverseType::FoldOrPartial => break,
+                    _ => return None,
+                }
+            },
             None => {
                 match traverse_type {
                     TraverseType::FoldOrPartial if !stack.is_empty() => break,
@@ -181,15 +197,15 @@ pub(crate) fn traverse<'a, 'b, K, V>(node: &'a Node<K, V>, token: &'b [u8], trav
 }
 
 // Helper function to push traverse info onto stack 
-fn traverse_fold_helper<'a, 's, K, V>(node: &'a Node<K, V>, level: u32,
-                                    stack: &'s mut TraverseStack<'a, K, V>, traverse_type: TraverseType) {
+fn traverse_fold_helper<'a, K, V, A>(node: &'a Node<K, V, A>, level: u32,
+                                    stack: &mut TraverseStack<'a, K, V, A>, traverse_type: TraverseType) {
     match traverse_type {
         TraverseType::Fold | TraverseType::FoldOrPartial => {
             if let Some(common) = node.label() {
                 // Grab top element on stack, if present,
                 // set prior next_key given that it is available as the current label's first byte
                 if let Some(item) = stack.last_mut() {
-                    item.next_key = *common.get(0).unwrap()
+                    item.next_key = *common.first().unwrap()
                 }
             }
 
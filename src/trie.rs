@@ -1,47 +1,87 @@
-use std::convert::AsRef;
 use std::borrow::Cow;
+use std::mem;
+use std::io::Read;
 
 use crate::node::Node;
-use crate::query::{longest_prefix, all_keys};
+use crate::aggregate::TrieAggregate;
+use crate::key::{TrieKey, decode_stored};
+use crate::node::algebra::{Rules, combine, combine_ref, split_off};
+use crate::segment::best_segmentation;
+use crate::query::{longest_prefix, longest_prefix_match, shortest_prefix, prefixes_of, all_keys, rank, select, count_prefix, aggregate_prefix};
 //use crate::iter::{ValuesIter, ValuesIterMut, IntoIter, LeafPairsIter, LeafPairsIterMut};
 
-pub type LabelsIter<'a, K, V> = crate::iter::LabelsIter<'a, K, V>;
-pub type ValuesIter<'a, K, V> = crate::iter::ValuesIter<'a, K, V>;
-pub type ValuesIterMut<'a, K, V> = crate::iter::ValuesIterMut<'a, K, V>;
-pub type LeafPairsIter<'a, K, V> = crate::iter::LeafPairsIter<'a, K, V>;
-pub type LeafPairsIterMut<'a, K, V> = crate::iter::LeafPairsIterMut<'a, K, V>;
-pub type IntoIter<K, V> = crate::iter::IntoIter<K, V>;
-
-#[derive(Debug)]
-pub struct Trie<K, V> {
+pub type LabelsIter<'a, K, V, A = ()> = crate::iter::LabelsIter<'a, K, V, A>;
+pub type ValuesIter<'a, K, V, A = ()> = crate::iter::ValuesIter<'a, K, V, A>;
+pub type ValuesIterMut<'a, K, V, A = ()> = crate::iter::ValuesIterMut<'a, K, V, A>;
+pub type LeafPairsIter<'a, K, V, A = ()> = crate::iter::LeafPairsIter<'a, K, V, A>;
+pub type LeafPairsIterMut<'a, K, V, A = ()> = crate::iter::LeafPairsIterMut<'a, K, V, A>;
+pub type IntoIter<K, V, A = ()> = crate::iter::IntoIter<K, V, A>;
+pub type KeysIter<'a, K, V, A = ()> = crate::iter::KeysIter<'a, K, V, A>;
+pub type PairsIter<'a, K, V, A = ()> = crate::iter::PairsIter<'a, K, V, A>;
+pub type TypedKeysIter<'a, K, V, A = ()> = crate::iter::TypedKeysIter<'a, K, V, A>;
+pub type TypedPairsIter<'a, K, V, A = ()> = crate::iter::TypedPairsIter<'a, K, V, A>;
+pub type TypedIntoIter<K, V, A = ()> = crate::iter::TypedIntoIter<K, V, A>;
+pub type PrefixesIter<'a, K, V, A = ()> = crate::query::PrefixesIter<'a, K, V, A>;
+pub type Cursor<'a, K, V, A = ()> = crate::cursor::Cursor<'a, K, V, A>;
+pub type CursorMut<'a, K, V, A = ()> = crate::cursor::CursorMut<'a, K, V, A>;
+pub type Subtrie<'a, K, V, A = ()> = crate::subtrie::Subtrie<'a, K, V, A>;
+pub type SubtrieMut<'a, K, V, A = ()> = crate::subtrie::SubtrieMut<'a, K, V, A>;
+pub type Matcher<'a, V> = crate::matcher::Matcher<'a, V>;
+pub type MatchKind = crate::matcher::MatchKind;
+pub type FindIter<'m, 't, 'a, V> = crate::matcher::FindIter<'m, 't, 'a, V>;
+pub type Token<T, V> = crate::tokenize::Token<T, V>;
+pub type Tokens<'a, 't, K, V, A = ()> = crate::tokenize::Tokens<'a, 't, K, V, A>;
+pub type ReaderTokens<'a, R, K, V, A = ()> = crate::tokenize::ReaderTokens<'a, R, K, V, A>;
+pub type Segmentations<'a, 't, V> = crate::segment::Segmentations<'a, 't, V>;
+pub type Segmentation<'t, 'a, V> = crate::segment::Segmentation<'t, 'a, V>;
+
+#[derive(Clone, Debug)]
+pub struct Trie<K, V, A = ()> {
     size: usize,
-    root: Option<Node<K, V>>,
+    root: Option<Node<K, V, A>>,
+    dirty: Dirty, // aggregates needing a write-back after values were handed out mutably
+}
+
+// Paths whose summaries are pending a refresh, or the whole trie once
+// values were handed out through an iterator
+#[derive(Clone, Debug, Default)]
+enum Dirty {
+    #[default]
+    Clean,
+    Paths(Vec<Vec<u8>>),
+    All,
 }
 
-impl<K, V> Trie<K, V>
+impl<K, V, A: TrieAggregate<V>> Trie<K, V, A>
 {
     pub fn new() -> Self {
-        Trie { size: 0, root: None }
+        Trie { size: 0, root: None, dirty: Dirty::Clean }
     }
 
     // Retrieves key's stored value
     // (key is not in fact stored only its fragments)
     pub fn search(&self, token: K) -> Option<&'_ V>
-    where K: AsRef<[u8]> 
+    where K: TrieKey 
     {
-        self.root.as_ref().and_then(|n| n.search(token.as_ref()))
+        self.root.as_ref().and_then(|n| n.search(&token.encode()))
     }
 
     // Inserts value into Trie along with a key fragment if not already resident
-    pub fn insert<T>(&mut self, token: T, value: V) -> Option<V>
-    where T: AsRef<[u8]>
+    pub fn insert(&mut self, token: K, value: V) -> Option<V>
+    where K: TrieKey
     {
+        self.insert_bytes(token.encode(), value)
+    }
+
+    // Inserts value using the key's encoded bytes, the empty key is stored at the root
+    pub(crate) fn insert_bytes(&mut self, token: Cow<[u8]>, value: V) -> Option<V> {
+        self.refresh_aggregates();
+
         if self.root.is_none() {
             self.root = Some(Node::default());
         }
 
-        let token_cow: Cow<[u8]> = token.as_ref().into();
-        let result = self.root.as_mut().and_then(|n| n.insert(token_cow, value));
+        let result = self.root.as_mut().and_then(|n| n.insert(token, value));
 
         if result.is_none() {
             self.size += 1
@@ -50,18 +90,84 @@ impl<K, V> Trie<K, V>
         result
     }
 
+    // Retrieves mut value using the key's encoded bytes, first inserting default's value for a
+    // new key, so existing values are updated in place rather than removed and reinserted
+    pub(crate) fn get_or_insert_with_bytes<F>(&mut self, token: &[u8], default: F) -> &'_ mut V
+    where F: FnOnce() -> V
+    {
+        self.refresh_aggregates();
+        self.touch(token);
+
+        let root = self.root.get_or_insert_with(Node::default);
+        let (value, inserted) = root.get_or_insert_with(token, default);
+
+        self.size += inserted as usize;
+        value
+    }
+
+    // Retrieves mut value using the key's encoded bytes, updating it in place
+    pub(crate) fn search_mut_bytes(&mut self, token: &[u8]) -> Option<&'_ mut V> {
+        self.touch(token);
+        self.root.as_mut().and_then(|n| n.search_mut(token))
+    }
+
     // Returns iterator of longest prefix of token that exists in trie
     pub fn longest_prefix(&self, token: K) -> Option<impl Iterator<Item = &'_ u8>>
-    where K: AsRef<[u8]>   //Option<String> {
+    where K: TrieKey   //Option<String> {
     {
-        self.root.as_ref().and_then(|n| longest_prefix(n, token.as_ref()))
+        self.root.as_ref().and_then(|n| longest_prefix(n, &token.encode()))
+    }
+
+    // Returns length and value of the longest key that is a prefix of token,
+    // the matched key is token[..length]
+    pub fn longest_prefix_match(&self, token: K) -> Option<(usize, &'_ V)>
+    where K: TrieKey
+    {
+        self.longest_prefix_match_bytes(&token.encode())
+    }
+
+    fn longest_prefix_match_bytes(&self, token: &[u8]) -> Option<(usize, &'_ V)> {
+        self.root.as_ref().and_then(|n| longest_prefix_match(n, token))
+    }
+
+    // Returns length and mut value of the longest key that is a prefix of token
+    pub fn longest_prefix_match_mut(&mut self, token: K) -> Option<(usize, &'_ mut V)>
+    where K: TrieKey
+    {
+        let token = token.encode();
+        let (len, _) = self.longest_prefix_match_bytes(&token)?;
+
+        self.touch(&token[..len]);
+        self.root.as_mut().and_then(|n| n.longest_prefix_match_mut(&token))
+    }
+
+    // Returns length and value of the shortest key that is a prefix of token,
+    // the matched key is token[..length]
+    pub fn shortest_prefix(&self, token: K) -> Option<(usize, &'_ V)>
+    where K: TrieKey
+    {
+        self.root.as_ref().and_then(|n| shortest_prefix(n, &token.encode()))
+    }
+
+    // Iterate through all keys which are prefixes of token, shortest first,
+    // yielding each key's length along with its value
+    pub fn prefixes_of(&self, token: K) -> PrefixesIter<'_, K, V, A>
+    where K: TrieKey
+    {
+        self.root.as_ref().map_or_else(
+            PrefixesIter::default, |n| prefixes_of(n, &token.encode())
+        )
     }
 
     // Returns all keys which share a common token prefix
     pub fn all_keys(&self, token: K) -> Option<Vec<Vec<u8>>>
-    where K: AsRef<[u8]>
+    where K: TrieKey
     {
-        self.root.as_ref().and_then(|n| all_keys(n, token.as_ref()))
+        self.root.as_ref().and_then(|n| all_keys(n, &token.encode()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
     }
 
     pub fn is_empty(&self) -> bool {
@@ -73,12 +179,42 @@ impl<K, V> Trie<K, V>
         self.size = 0
     }
 
+    // Removes all keys starting with token prefix in one operation by detaching the
+    // matching subtree, the subtree's parent is pruned or merged as necessary.
+    // Returns the removed keys as a separate trie
+    pub fn remove_prefix(&mut self, token: K) -> Trie<K, V, A>
+    where K: TrieKey
+    {
+        let token = token.encode();
+
+        if token.is_empty() {
+            return mem::take(self)
+        }
+
+        self.refresh_aggregates();
+
+        match self.root.as_mut().and_then(|n| n.detach_prefix(&token)) {
+            Some(subtree) => {
+                let count = subtree.1.key_count();
+                self.size -= count;
+                Trie { size: count, root: Some(Node::from_subtree(subtree)), dirty: Dirty::Clean }
+            },
+            None => Trie::new(),
+        }
+    }
+
     // Removes token value and leftover key fragments as necessary
     // to the extent of possibly pruning or merging nodes
     pub fn remove(&mut self, token: K) -> Option<V>
-    where K: AsRef<[u8]>
+    where K: TrieKey
     {
-        let result = self.root.as_mut().and_then(|n| n.remove(token.as_ref()));
+        self.remove_bytes(&token.encode())
+    }
+
+    pub(crate) fn remove_bytes(&mut self, token: &[u8]) -> Option<V> {
+        self.refresh_aggregates();
+
+        let result = self.root.as_mut().and_then(|n| n.remove(token));
 
         if result.is_some() {
             self.size -= 1
@@ -87,72 +223,428 @@ impl<K, V> Trie<K, V>
         result
     }
 
+    // Returns a borrowed view of the keys starting with token prefix,
+    // all view operations take keys relative to the prefix
+    pub fn subtrie(&self, token: K) -> Subtrie<'_, K, V, A>
+    where K: TrieKey
+    {
+        Subtrie::new(self, token.encode().into_owned())
+    }
+
+    // Returns a mutably borrowed view of the keys starting with token prefix
+    pub fn subtrie_mut(&mut self, token: K) -> SubtrieMut<'_, K, V, A>
+    where K: TrieKey
+    {
+        SubtrieMut::new(self, token.encode().into_owned())
+    }
+
+    // Returns a cursor, initially at the ghost position before the first key
+    pub fn cursor(&self) -> Cursor<'_, K, V, A> {
+        Cursor::new(self.root.as_ref())
+    }
+
+    // Returns a mutable cursor, initially at the ghost position before the first key
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, K, V, A> {
+        CursorMut::new(self)
+    }
+
+    // Structurally combines two tries by walking both tries' nodes in lockstep,
+    // the rules decide which of the keys survive into the resulting trie
+    pub(crate) fn combine<F>(mut self, mut other: Trie<K, V, A>, mut rules: Rules<F>) -> Trie<K, V, A>
+    where F: FnMut(V, V) -> Option<V>
+    {
+        self.refresh_aggregates();
+        other.refresh_aggregates();
+
+        let left = self.root.unwrap_or_default();
+        let right = other.root.unwrap_or_default();
+
+        let root = combine(left, right, &mut rules);
+
+        Trie { size: root.key_count(), root: Some(root), dirty: Dirty::Clean }
+    }
+
+    // Combines two borrowed tries like combine, cloning only what survives into the result.
+    // Cloned subtrees carry over their summaries, so pending write-backs spread to the result
+    pub(crate) fn combine_ref<F>(&self, other: &Trie<K, V, A>, mut rules: Rules<F>) -> Trie<K, V, A>
+    where K: Clone, V: Clone, A: Clone, F: FnMut(V, V) -> Option<V>
+    {
+        let empty = Node::default();
+        let left = self.root.as_ref().unwrap_or(&empty);
+        let right = other.root.as_ref().unwrap_or(&empty);
+
+        let root = combine_ref(left, right, &mut rules);
+        let dirty = match (&self.dirty, &other.dirty) {
+            (Dirty::Clean, Dirty::Clean) => Dirty::Clean,
+            _ => Dirty::All,
+        };
+
+        Trie { size: root.key_count(), root: Some(root), dirty }
+    }
+}
+
+// Define structural merge methods, both tries' nodes are walked in lockstep
+// splitting labels wherever the two tries' compressed labels diverge
+
+impl<K, V, A: TrieAggregate<V>> Trie<K, V, A> {
+    // Moves all of other's entries into trie, conflict_fn resolves keys found in
+    // both tries given the trie's value and other's value
+    pub fn merge<F>(&mut self, other: Trie<K, V, A>, mut conflict_fn: F)
+    where F: FnMut(V, V) -> V
+    {
+        let rules = Rules::new(true, true, |a, b| Some(conflict_fn(a, b)));
+        *self = mem::take(self).combine(other, rules);
+    }
+
+    // Retains only the keys found in both tries, combine_fn produces the retained value
+    // given the trie's value and other's value
+    pub fn intersect_with<F>(&mut self, other: Trie<K, V, A>, mut combine_fn: F)
+    where F: FnMut(V, V) -> V
+    {
+        let rules = Rules::new(false, false, |a, b| Some(combine_fn(a, b)));
+        *self = mem::take(self).combine(other, rules);
+    }
+
+    // Removes all keys which are also found in other
+    pub fn difference(&mut self, other: Trie<K, V, A>) {
+        let rules = Rules::new(true, false, |_, _| None);
+        *self = mem::take(self).combine(other, rules);
+    }
+
+    // Moves all of other's entries into trie leaving other empty,
+    // values of keys found in both tries are overwritten by other's values
+    pub fn append(&mut self, other: &mut Trie<K, V, A>) {
+        self.merge(mem::take(other), |_, b| b)
+    }
+
+    // Splits the trie in two, returning a new trie with all the keys
+    // lexicographically greater than or equal to token
+    pub fn split_off(&mut self, token: K) -> Trie<K, V, A>
+    where K: TrieKey
+    {
+        self.refresh_aggregates();
+
+        let Some(root) = self.root.as_mut() else {
+            return Trie::new()
+        };
+
+        let right = split_off(root, &token.encode());
+        let count = right.key_count();
+
+        self.size -= count;
+        Trie { size: count, root: Some(right), dirty: Dirty::Clean }
+    }
+}
+
+// Define aggregate methods, see TrieAggregate
+
+impl<K, V, A: TrieAggregate<V>> Trie<K, V, A> {
+    // Returns the combined summary of the values of all keys starting with token prefix,
+    // read off the cached summaries. None while a write-back is pending, i.e. after values
+    // were handed out mutably and until refresh_aggregates is called
+    pub fn aggregate_prefix(&self, token: K) -> Option<A>
+    where K: TrieKey, A: Clone
+    {
+        self.aggregate_prefix_bytes(&token.encode())
+    }
+
+    pub(crate) fn aggregate_prefix_bytes(&self, token: &[u8]) -> Option<A>
+    where A: Clone
+    {
+        if !matches!(self.dirty, Dirty::Clean) {
+            return None
+        }
+
+        Some(self.root.as_ref().map_or_else(A::empty, |n| aggregate_prefix(n, token)))
+    }
+
+    // Writes back the summaries after values were changed via values_mut, iter_mut or other
+    // mutable value refs. Only the paths of values handed out singly are refreshed, values
+    // handed out by an iterator need the whole trie refreshed. Done automatically on the
+    // next insert or remove
+    pub fn refresh_aggregates(&mut self) {
+        let (dirty, Some(root)) = (mem::take(&mut self.dirty), self.root.as_mut()) else {
+            return
+        };
+
+        match dirty {
+            Dirty::Clean => (),
+            Dirty::Paths(paths) => paths.iter().for_each(|p| root.refresh_path(p)),
+            Dirty::All => root.refresh_all(),
+        }
+    }
+
+    // Records that the value at key was handed out mutably
+    pub(crate) fn touch(&mut self, key: &[u8]) {
+        if !A::TRIVIAL {
+            self.mark_dirty(key);
+        }
+    }
+
+    // Records that any of the values may have been changed
+    fn touch_all(&mut self) {
+        if !A::TRIVIAL {
+            self.dirty = Dirty::All;
+        }
+    }
+}
+
+// Define ordered access methods, keys are ordered lexicographically by their bytes
+
+impl<K, V, A: TrieAggregate<V>> Trie<K, V, A> {
+    // Returns the first key and its value
+    pub fn first_key_value(&self) -> Option<(K::Owned, &'_ V)>
+    where K: TrieKey
+    {
+        self.cursor().seek_first()
+    }
+
+    // Returns the last key and its value
+    pub fn last_key_value(&self) -> Option<(K::Owned, &'_ V)>
+    where K: TrieKey
+    {
+        self.cursor().seek_last()
+    }
+
+    // Returns the nearest key strictly greater than token (token need not be stored)
+    pub fn successor(&self, token: K) -> Option<(K::Owned, &'_ V)>
+    where K: TrieKey
+    {
+        let token = token.encode();
+        let mut cursor = self.cursor();
+        cursor.seek_bytes(&token);
+
+        // Seek lands on the first key >= token, only step if that is the token itself
+        match cursor.key_bytes() {
+            Some(key) if key == &*token => cursor.next(),
+            Some(_) => cursor.key().zip(cursor.value()),
+            None => None,
+        }
+    }
+
+    // Returns the nearest key strictly less than token (token need not be stored)
+    pub fn predecessor(&self, token: K) -> Option<(K::Owned, &'_ V)>
+    where K: TrieKey
+    {
+        let mut cursor = self.cursor();
+        cursor.seek_bytes(&token.encode());
+
+        // Stepping back from the first key >= token, or from the ghost position
+        // when every key is less than token, yields the predecessor
+        cursor.prev()
+    }
+
+    // Returns the number of keys strictly less than token, i.e. token's position
+    // in key order were it stored
+    pub fn rank(&self, token: K) -> usize
+    where K: TrieKey
+    {
+        self.root.as_ref().map_or(0, |n| rank(n, &token.encode()))
+    }
+
+    // Returns the key and value at position n in key order (zero based)
+    pub fn select(&self, n: usize) -> Option<(K::Owned, &'_ V)>
+    where K: TrieKey
+    {
+        self.root.as_ref().and_then(|r| select(r, n)).map(|(k, v)| (decode_stored::<K>(&k), v))
+    }
+
+    // Returns the number of keys which start with token prefix, answered from the cached
+    // key counts without enumerating the keys
+    pub fn count_prefix(&self, token: K) -> usize
+    where K: TrieKey
+    {
+        self.root.as_ref().map_or(0, |n| count_prefix(n, &token.encode()))
+    }
+
+    // Returns true if any key starts with token prefix
+    pub fn has_prefix(&self, token: K) -> bool
+    where K: TrieKey
+    {
+        self.count_prefix(token) > 0
+    }
+
+    // Removes and returns the first key and its value
+    pub fn pop_first(&mut self) -> Option<(K::Owned, V)>
+    where K: TrieKey
+    {
+        let key = self.cursor().next_bytes()?.0.to_vec();
+        self.remove_bytes(&key).map(|v| (decode_stored::<K>(&key), v))
+    }
+
+    // Removes and returns the last key and its value
+    pub fn pop_last(&mut self) -> Option<(K::Owned, V)>
+    where K: TrieKey
+    {
+        let key = self.cursor().prev_bytes()?.0.to_vec();
+        self.remove_bytes(&key).map(|v| (decode_stored::<K>(&key), v))
+    }
+}
+
+// Define root access, values reached mutably through the root bypass the aggregates,
+// callers handing such values out record them via touch
+
+impl<K, V, A> Trie<K, V, A> {
     #[allow(dead_code)]
-    pub(crate) fn root(&self) -> Option<&Node<K, V>> {
+    pub(crate) fn root(&self) -> Option<&Node<K, V, A>> {
         self.root.as_ref()
     }
+
+    // Hands the root over to a mutable cursor along with the size, the trie reads as empty
+    // until both are restored, so a leaked cursor leaves an empty trie behind
+    pub(crate) fn take_root(&mut self) -> (Option<Node<K, V, A>>, usize) {
+        (self.root.take(), mem::take(&mut self.size))
+    }
+
+    // Takes the root back from a mutable cursor, along with the keys whose values it handed out
+    pub(crate) fn restore_root(&mut self, root: Node<K, V, A>, size: usize, touched: Vec<Vec<u8>>) {
+        self.root = Some(root);
+        self.size = size;
+        touched.iter().for_each(|key| self.mark_dirty(key));
+    }
+
+    // Records key's path as pending a write-back, see touch
+    fn mark_dirty(&mut self, key: &[u8]) {
+        match &mut self.dirty {
+            Dirty::Clean => self.dirty = Dirty::Paths(vec![key.to_vec()]),
+            // Past one path per key a full refresh is cheaper
+            Dirty::Paths(paths) if paths.len() >= self.size => self.dirty = Dirty::All,
+            Dirty::Paths(paths) => paths.push(key.to_vec()),
+            Dirty::All => (),
+        }
+    }
+
+    // Builds an automaton finding occurrences of the trie's keys within a text
+    pub fn build_matcher(&self) -> Matcher<'_, V> {
+        Matcher::new(self.root.as_ref())
+    }
+
+    // Splits input into tokens, repeatedly taking the longest key at the current position
+    pub fn tokenize<'t, T>(&self, input: &'t T) -> Tokens<'_, 't, K, V, A>
+    where T: AsRef<[u8]> + ?Sized
+    {
+        Tokens::new(self.root.as_ref(), input.as_ref())
+    }
+
+    // Streaming tokenize, reads are buffered internally so any Read can be passed
+    pub fn tokenize_reader<R: Read>(&self, reader: R) -> ReaderTokens<'_, R, K, V, A> {
+        ReaderTokens::new(self.root.as_ref(), reader)
+    }
+
+    // Iterates every way of splitting input into a sequence of keys
+    pub fn segment<'t, T>(&self, input: &'t T) -> Segmentations<'_, 't, V>
+    where T: AsRef<[u8]> + ?Sized
+    {
+        Segmentations::new(self.root.as_ref(), input.as_ref())
+    }
+
+    // Finds the split of input into keys with the highest total score, each key scored
+    // by score_fn e.g. using stored values as weights. Returns the total with the keys
+    pub fn best_segmentation<'t, T, F>(&self, input: &'t T, score_fn: F) -> Option<(f64, Segmentation<'t, '_, V>)>
+    where T: AsRef<[u8]> + ?Sized, F: FnMut(&[u8], &V) -> f64
+    {
+        best_segmentation(self.root.as_ref(), input.as_ref(), score_fn)
+    }
 }
 
-impl<K, V> Default for Trie<K, V> {
-    fn default() -> Trie<K, V> {
+impl<K, V, A: TrieAggregate<V>> Default for Trie<K, V, A> {
+    fn default() -> Trie<K, V, A> {
         Trie::new()
     }
 }
 
 // Define iterator methods
 
-impl<K, V> Trie<K, V> {
+impl<K, V, A: TrieAggregate<V>> Trie<K, V, A> {
     // General reference iterator over all elements in the trie
-    pub fn iter(&self) -> LeafPairsIter<'_, K, V> {
+    pub fn iter(&self) -> LeafPairsIter<'_, K, V, A> {
         self.root.as_ref().map_or_else(
             LeafPairsIter::default, |r| r.iter(self.size)
         )
     }
 
     // General mut reference iterator over all elements in the trie
-    pub fn iter_mut(&mut self) -> LeafPairsIterMut<'_, K, V> {
+    pub fn iter_mut(&mut self) -> LeafPairsIterMut<'_, K, V, A> {
+        let size = self.size;
+        self.touch_all();
         self.root.as_mut().map_or_else(
-            LeafPairsIterMut::default, |r| r.iter_mut(self.size)
+            LeafPairsIterMut::default, |r| r.iter_mut(size)
         )
     }
 
+    // Iterate through all trie's keys and values, each key reassembled from its labels
+    // and decoded back into the key type
+    pub fn pairs(&self) -> TypedPairsIter<'_, K, V, A>
+    where K: TrieKey
+    {
+        self.root.as_ref().map_or_else(
+            PairsIter::default, |r| r.pairs(self.size)
+        ).into()
+    }
+
+    // Iterate through all trie's keys, each key reassembled from its labels
+    // and decoded back into the key type
+    pub fn keys(&self) -> TypedKeysIter<'_, K, V, A>
+    where K: TrieKey
+    {
+        self.root.as_ref().map_or_else(
+            KeysIter::default, |r| r.keys(self.size)
+        ).into()
+    }
+
+    // Empties the trie, iterating over the removed key value pairs
+    pub fn drain(&mut self) -> TypedIntoIter<K, V, A>
+    where K: TrieKey
+    {
+        mem::take(self).into_iter()
+    }
+
+    // Removes all keys starting with token prefix, iterating over the removed key value pairs
+    pub fn drain_prefix(&mut self, token: K) -> TypedIntoIter<K, V, A>
+    where K: TrieKey
+    {
+        self.remove_prefix(token).into_iter()
+    }
+
     // Iterate through trie's labels
-    pub fn labels(&self) -> LabelsIter<'_, K, V> {
+    pub fn labels(&self) -> LabelsIter<'_, K, V, A> {
         self.root.as_ref().map_or_else(
             LabelsIter::default, |r| r.labels(self.size)
         )
     }
 
     // Iterate through all trie's values
-    pub fn values(&self) -> ValuesIter<'_, K, V> {
+    pub fn values(&self) -> ValuesIter<'_, K, V, A> {
         self.root.as_ref().map_or_else(
             ValuesIter::default, |r| r.values(self.size)
         )
     }
 
     // Iterate through all trie's values mutably
-    pub fn values_mut(&mut self) -> ValuesIterMut<'_, K, V> {
+    pub fn values_mut(&mut self) -> ValuesIterMut<'_, K, V, A> {
+        let size = self.size;
+        self.touch_all();
         self.root.as_mut().map_or_else(
-            ValuesIterMut::default, |r| r.values_mut(self.size)
+            ValuesIterMut::default, |r| r.values_mut(size)
         )
     }
 }
 
-impl<K, V> IntoIterator for Trie<K, V> {
-    type IntoIter = IntoIter<K, V>;
-    type Item = V;
+impl<K: TrieKey, V, A> IntoIterator for Trie<K, V, A> {
+    type IntoIter = TypedIntoIter<K, V, A>;
+    type Item = (K::Owned, V);
 
     fn into_iter(self) -> Self::IntoIter {
         self.root.map_or_else(
             IntoIter::default, Node::into_iter
-        )
+        ).into()
     }
 }
 
-impl<K, V> FromIterator<(K, V)> for Trie<K, V>
-where K: AsRef<[u8]>
+impl<K, V, A> FromIterator<(K, V)> for Trie<K, V, A>
+where K: TrieKey, A: TrieAggregate<V>
 {
-    fn from_iter<I>(iter: I) -> Trie<K, V>
+    fn from_iter<I>(iter: I) -> Trie<K, V, A>
     where
         I: IntoIterator<Item = (K, V)>,
     {
@@ -173,7 +665,7 @@ mod tests {
     use super::*;
     use std::collections::{BTreeSet};
 
-    fn keys_helper<'a>(keys: Option<&'a Vec<Vec<u8>>>) -> Vec<&'a str> {
+    fn keys_helper(keys: Option<&Vec<Vec<u8>>>) -> Vec<&str> {
         if let Some(k) = keys {
             let mut v = k.iter().map(|bytes| std::str::from_utf8(bytes).unwrap()).collect::<Vec<_>>();
             v.sort_unstable();
@@ -183,12 +675,12 @@ mod tests {
         }
     }
 
-    fn labels_helper<'a, K: 'a, V: 'a>(labels: LabelsIter<'a, K, V>) -> BTreeSet<&'a str> {
+    fn labels_helper<'a, K: 'a, V: 'a, A: 'a>(labels: LabelsIter<'a, K, V, A>) -> BTreeSet<&'a str> {
         labels.map(|bytes| std::str::from_utf8(bytes).unwrap()).collect::<BTreeSet<&str>>()
     }
 
 /*
-    fn print_labels<'a, K: 'a, V: 'a>(labels: Labels<'a, K, V>) {
+    fn print_labels<'a, K: 'a, V: 'a, A: 'a>(labels: Labels<'a, K, V, A>) {
         println!("labels are {:?}", labels_helper(labels))
     }
 */
@@ -235,7 +727,7 @@ mod tests {
         let mut keys = trie.all_keys("ant").unwrap();
         keys.sort();
 
-        let nested = vec![
+        let nested = [
             vec![97, 110, 116, 104, 101, 109],
             vec![97, 110, 116, 104, 101, 109, 105, 111, 110],
             vec![97, 110, 116, 105]
@@ -261,6 +753,51 @@ mod tests {
     }
 
 
+    #[test]
+    fn check_longest_prefix_match() {
+        let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77), ("ant", 3)].iter().cloned().collect();
+
+        assert_eq!(Some((6, &1)), trie.longest_prefix_match("anthemio"));
+        assert_eq!(Some((9, &7)), trie.longest_prefix_match("anthemion"));
+
+        // token diverges in the middle of the "hem" label
+        assert_eq!(Some((3, &3)), trie.longest_prefix_match("anthx"));
+        let result = trie.longest_prefix("anthx").unwrap().cloned().collect::<Vec<_>>();
+        assert_eq!("ant", std::str::from_utf8(&result).unwrap());
+
+        assert_eq!(None, trie.longest_prefix_match("an"));
+        assert_eq!(None, trie.longest_prefix_match(""));
+
+        let (len, value) = trie.longest_prefix_match_mut("antique").unwrap();
+        assert_eq!(4, len);
+        *value += 40;
+        assert_eq!(&42, trie.search("anti").unwrap());
+        assert_eq!(None, trie.longest_prefix_match_mut("mouse"));
+    }
+
+    #[test]
+    fn check_prefixes_of() {
+        let trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77), ("a", 5)].iter().cloned().collect();
+
+        assert_eq!(vec![(1, &5), (6, &1), (9, &7)], trie.prefixes_of("anthemion").collect::<Vec<_>>());
+        assert_eq!(vec![(1, &5), (6, &1)], trie.prefixes_of("anthemix").collect::<Vec<_>>());
+        assert_eq!(vec![(1, &5)], trie.prefixes_of("antx").collect::<Vec<_>>());
+        assert_eq!(0, trie.prefixes_of("mouse").count());
+        assert_eq!(0, Trie::<&str, i32>::new().prefixes_of("anthem").count());
+    }
+
+    #[test]
+    fn check_shortest_prefix() {
+        let trie: Trie<_, _> = [("evil.com", 1), ("evil.com/anything/else", 2), ("evil.co", 3), ("good.org", 4)].iter().cloned().collect();
+
+        assert_eq!(Some((7, &3)), trie.shortest_prefix("evil.com/anything/else"));
+        assert_eq!(Some((7, &3)), trie.shortest_prefix("evil.co"));
+        assert_eq!(Some((8, &4)), trie.shortest_prefix("good.org/path"));
+        assert_eq!(None, trie.shortest_prefix("evil.c"));
+        assert_eq!(None, trie.shortest_prefix("good.net"));
+        assert_eq!(None, trie.shortest_prefix(""));
+    }
+
     #[test]
     fn passthru_removes() {
         let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().cloned().collect();
@@ -319,9 +856,49 @@ mod tests {
         assert_eq!(trie.remove("and").unwrap(), 77);
         assert_eq!(trie.all_keys("an"), None);
         assert_eq!(trie.remove("nonexistent2"), None);
-        assert_eq!(trie.is_empty(), true);
+        assert!(trie.is_empty());
+    }
+
+
+    #[test]
+    fn remove_leaf_below_root() {
+        let mut trie: Trie<_, _> = [("abc", 1), ("ca", 2)].iter().cloned().collect();
+
+        // root is never merged as a passthrough node
+        assert_eq!(Some(1), trie.remove("abc"));
+        assert_eq!(&2, trie.search("ca").unwrap());
+        assert_eq!(Some(2), trie.remove("ca"));
+        assert!(trie.is_empty());
     }
 
+    #[test]
+    fn check_empty_key() {
+        let mut trie: Trie<_, _> = [("and", 1), ("", 2)].iter().cloned().collect();
+
+        // The empty key is held by the root and prefixes every token
+        assert_eq!(2, trie.len());
+        assert_eq!(Some(&2), trie.search(""));
+        assert_eq!(Some((0, &2)), trie.longest_prefix_match("ant"));
+        assert_eq!(Some((0, &2)), trie.shortest_prefix("and"));
+        assert_eq!(vec![(0, &2), (3, &1)], trie.prefixes_of("andy").collect::<Vec<_>>());
+        assert_eq!(Vec::<u8>::new(), trie.longest_prefix("ant").unwrap().copied().collect::<Vec<u8>>());
+        assert_eq!(b"and".to_vec(), trie.longest_prefix("andy").unwrap().copied().collect::<Vec<u8>>());
+
+        // Ordered first, and root passthrough merging still never happens
+        assert_eq!(Some(("".to_string(), &2)), trie.first_key_value());
+        assert_eq!(Some(("".to_string(), &2)), trie.predecessor("and"));
+        assert_eq!(Some(("and".to_string(), &1)), trie.successor(""));
+        assert_eq!(Some(2), trie.insert("", 3));
+        assert_eq!(Some(3), trie.remove(""));
+        assert_eq!(None, trie.remove(""));
+        assert_eq!(Some(&1), trie.search("and"));
+        assert_eq!(1, trie.len());
+
+        trie.insert("", 4);
+        assert_eq!(Some(("".to_string(), 4)), trie.pop_first());
+        assert_eq!(Some(("and".to_string(), 1)), trie.pop_last());
+        assert!(trie.is_empty());
+    }
 
     #[test]
     fn check_compressed_labels() {
@@ -352,11 +929,97 @@ mod tests {
     }
 
 
+    #[test]
+    fn check_merge() {
+        let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("and", 77)].iter().cloned().collect();
+        let other: Trie<_, _> = [("ant", 3), ("anthemion", 7), ("anti", 40)].iter().cloned().collect();
+
+        trie.merge(other, |a, b| a + b);
+
+        assert_eq!(5, trie.len());
+        assert_eq!(&3, trie.search("ant").unwrap());
+        assert_eq!(&42, trie.search("anti").unwrap());
+        assert_eq!(&7, trie.search("anthemion").unwrap());
+
+        let set = labels_helper(trie.labels());
+        assert_eq!(set, BTreeSet::from(["an", "d", "t", "hem", "i", "ion"]));
+    }
+
+    #[test]
+    fn check_split_off_and_append() {
+        let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77), ("ant", 3)].iter().cloned().collect();
+
+        let mut right = trie.split_off("anthemia");
+
+        assert_eq!(3, trie.len());
+        assert_eq!(2, right.len());
+        assert_eq!(vec!["and", "ant", "anthem"], keys_helper(trie.all_keys("an").as_ref()));
+        assert_eq!(vec!["anthemion", "anti"], keys_helper(right.all_keys("an").as_ref()));
+        assert_eq!(labels_helper(trie.labels()), BTreeSet::from(["an", "d", "t", "hem"]));
+        assert_eq!(labels_helper(right.labels()), BTreeSet::from(["ant", "hemion", "i"]));
+
+        // split at an existing key keeps the key in the new trie
+        let rest = right.split_off("anti");
+        assert_eq!(vec!["anti"], keys_helper(rest.all_keys("an").as_ref()));
+        assert_eq!(1, right.len());
+
+        trie.append(&mut right);
+        assert_eq!(4, trie.len());
+        assert!(right.is_empty());
+        assert_eq!(&7, trie.search("anthemion").unwrap());
+
+        assert_eq!(4, trie.split_off("").len());
+        assert!(trie.is_empty());
+    }
+
+    #[test]
+    fn check_intersect_and_difference() {
+        let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().cloned().collect();
+        let mut trie2 = trie.clone();
+        let other: Trie<_, _> = [("ant", 3), ("anthemion", 10), ("anti", 40)].iter().cloned().collect();
+
+        trie.intersect_with(other.clone(), |a, b| a * b);
+
+        assert_eq!(2, trie.len());
+        assert_eq!(&70, trie.search("anthemion").unwrap());
+        assert_eq!(&80, trie.search("anti").unwrap());
+        assert_eq!(None, trie.search("ant"));
+        assert_eq!(labels_helper(trie.labels()), BTreeSet::from(["ant", "hemion", "i"]));
+
+        trie2.difference(other);
+
+        assert_eq!(2, trie2.len());
+        assert_eq!(vec!["and", "anthem"], keys_helper(trie2.all_keys("an").as_ref()));
+        assert_eq!(labels_helper(trie2.labels()), BTreeSet::from(["an", "d", "them"]));
+    }
+
+    #[test]
+    fn check_remove_prefix() {
+        let mut trie: Trie<_, _> = [("tenant42/a", 1), ("tenant42/b/c", 2), ("tenant4", 3), ("tenant7/a", 4)].iter().cloned().collect();
+
+        let mut removed = trie.remove_prefix("tenant42/");
+        assert_eq!(2, removed.len());
+        assert_eq!(2, trie.len());
+        assert_eq!(&2, removed.search("tenant42/b/c").unwrap());
+        assert_eq!(&3, trie.search("tenant4").unwrap());
+        assert_eq!(None, trie.search("tenant42/a"));
+
+        // parent passthrough node is merged with its remaining child
+        assert_eq!(labels_helper(trie.labels()), BTreeSet::from(["tenant", "4", "7/a"]));
+
+        assert_eq!(Some(1), removed.remove("tenant42/a"));
+        assert!(trie.remove_prefix("tenant9").is_empty());
+
+        let all = trie.remove_prefix("");
+        assert_eq!(2, all.len());
+        assert!(trie.is_empty());
+    }
+
     #[test]
     fn check_values_iter() {
         let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().cloned().collect();
 
-        let _ = trie.values_mut().map(|v| { *v = *v * 5; v } ).collect::<BTreeSet<&mut i32>>();
+        let _ = trie.values_mut().map(|v| { *v *= 5; v } ).collect::<BTreeSet<&mut i32>>();
         assert_eq!(5, trie.remove("anthem").unwrap());
 
         let set2 = trie.values().collect::<BTreeSet<&i32>>();
@@ -366,10 +1029,305 @@ mod tests {
     #[test]
     fn check_values_into_iter() {
         let trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().cloned().collect();
-        let vec1 = trie.into_iter().map(|mut v| { v = v + 1; v } ).collect::<BTreeSet<i32>>();
+        let vec1 = trie.into_iter().map(|(_, mut v)| { v += 1; v } ).collect::<BTreeSet<i32>>();
         assert_eq!(vec1, BTreeSet::from([2, 3, 8, 78]));
     }
 
+    #[test]
+    fn check_pairs_into_iter() {
+        let trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().cloned().collect();
+        let set = trie.into_iter().collect::<BTreeSet<(String, i32)>>();
+        assert_eq!(BTreeSet::from([("and".to_string(), 77), ("anthem".to_string(), 1), ("anthemion".to_string(), 7), ("anti".to_string(), 2)]), set);
+    }
+
+    #[test]
+    fn check_drain() {
+        let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().cloned().collect();
+
+        // prefix ends in the middle of the "hem" label
+        let drained = trie.drain_prefix("anth").collect::<BTreeSet<(String, i32)>>();
+        assert_eq!(BTreeSet::from([("anthem".to_string(), 1), ("anthemion".to_string(), 7)]), drained);
+        assert_eq!(2, trie.len());
+        assert_eq!(labels_helper(trie.labels()), BTreeSet::from(["an", "d", "ti"]));
+
+        assert_eq!(0, trie.drain_prefix("antx").count());
+        assert_eq!(2, trie.len());
+
+        let drained = trie.drain().collect::<BTreeSet<(String, i32)>>();
+        assert_eq!(BTreeSet::from([("and".to_string(), 77), ("anti".to_string(), 2)]), drained);
+        assert!(trie.is_empty());
+        assert_eq!(None, trie.search("and"));
+    }
+
+    #[test]
+    fn check_ordered_access() {
+        let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().cloned().collect();
+
+        assert_eq!(Some(("and".to_string(), &77)), trie.first_key_value());
+        assert_eq!(Some(("anti".to_string(), &2)), trie.last_key_value());
+
+        assert_eq!(Some(("and".to_string(), 77)), trie.pop_first());
+        assert_eq!(Some(("anti".to_string(), 2)), trie.pop_last());
+        assert_eq!(Some(("anthemion".to_string(), 7)), trie.pop_last());
+        assert_eq!(Some(("anthem".to_string(), 1)), trie.pop_first());
+        assert_eq!(None, trie.pop_first());
+        assert!(trie.is_empty());
+    }
+
+    #[test]
+    fn check_successor_predecessor() {
+        let trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77), ("ant", 3)].iter().cloned().collect();
+
+        assert_eq!(Some(("anthemion".to_string(), &7)), trie.successor("anthem"));
+        assert_eq!(Some(("anthem".to_string(), &1)), trie.successor("anth"));
+        assert_eq!(Some(("anti".to_string(), &2)), trie.successor("anthz"));
+        assert_eq!(Some(("and".to_string(), &77)), trie.successor(""));
+        assert_eq!(None, trie.successor("anti"));
+        assert_eq!(None, trie.successor("b"));
+
+        assert_eq!(Some(("ant".to_string(), &3)), trie.predecessor("anthem"));
+        assert_eq!(Some(("anthemion".to_string(), &7)), trie.predecessor("anthz"));
+        assert_eq!(Some(("and".to_string(), &77)), trie.predecessor("ane"));
+        assert_eq!(Some(("anti".to_string(), &2)), trie.predecessor("b"));
+        assert_eq!(None, trie.predecessor("and"));
+        assert_eq!(None, trie.predecessor(""));
+        assert_eq!(None, Trie::<&str, i32>::new().predecessor("a"));
+    }
+
+    #[test]
+    fn check_order_statistics() {
+        let mut trie: Trie<_, _> = [("mouse", 1), ("mousepad", 2), ("moose", 3), ("map", 4), ("anti", 5), ("zebra", 6)].iter().cloned().collect();
+
+        assert_eq!(0, trie.rank("anti"));
+        assert_eq!(2, trie.rank("moose"));
+        assert_eq!(4, trie.rank("mousepad"));
+        assert_eq!(4, trie.rank("mousee"));
+        assert_eq!(5, trie.rank("mz"));
+        assert_eq!(6, trie.rank("zz"));
+
+        assert_eq!(Some(("anti".to_string(), &5)), trie.select(0));
+        assert_eq!(Some(("mousepad".to_string(), &2)), trie.select(4));
+        assert_eq!(None, trie.select(6));
+
+        assert_eq!(4, trie.count_prefix("m"));
+        assert_eq!(2, trie.count_prefix("mou"));
+        assert_eq!(6, trie.count_prefix(""));
+        assert_eq!(0, trie.count_prefix("mp"));
+
+        // Page through the keys under a prefix
+        let start = trie.rank("mo");
+        let page = (start..start + trie.count_prefix("mo")).map(|i| trie.select(i).unwrap().0).collect::<Vec<_>>();
+        assert_eq!(vec!["moose".to_string(), "mouse".to_string(), "mousepad".to_string()], page);
+
+        // Counts are kept up to date through removes and merges
+        trie.remove("mouse");
+        trie.remove("map");
+        assert_eq!(2, trie.count_prefix("mo"));
+        assert_eq!(Some(("mousepad".to_string(), &2)), trie.select(2));
+        assert_eq!(1, trie.remove_prefix("mou").len());
+        assert_eq!(3, trie.count_prefix(""));
+        assert_eq!(2, trie.rank("zebra"));
+    }
+
+    #[test]
+    fn check_count_and_has_prefix() {
+        let mut trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77), ("ant", 3)].iter().cloned().collect();
+
+        assert_eq!(4, trie.count_prefix("ant"));
+        assert_eq!(2, trie.count_prefix("anthe"));
+        assert_eq!(trie.all_keys("an").map(|v| v.len()).unwrap(), trie.count_prefix("an"));
+        assert!(trie.has_prefix("anthemi"));
+        assert!(trie.has_prefix(""));
+        assert!(!trie.has_prefix("antz"));
+        assert!(!trie.has_prefix("anthemions"));
+
+        trie.remove("anthemion");
+        assert!(!trie.has_prefix("anthemi"));
+        assert_eq!(1, trie.count_prefix("anthe"));
+        assert!(!Trie::<&str, i32>::new().has_prefix(""));
+    }
+
+    // Sum and max aggregates over values
+    #[derive(Clone, Debug, PartialEq)]
+    struct Sum(i32);
+
+    impl TrieAggregate<i32> for Sum {
+        fn empty() -> Self { Sum(0) }
+        fn lift(value: &i32) -> Self { Sum(*value) }
+        fn combine(&self, other: &Self) -> Self { Sum(self.0 + other.0) }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Max(Option<i32>);
+
+    impl TrieAggregate<i32> for Max {
+        fn empty() -> Self { Max(None) }
+        fn lift(value: &i32) -> Self { Max(Some(*value)) }
+        fn combine(&self, other: &Self) -> Self { Max(self.0.max(other.0)) }
+    }
+
+    #[test]
+    fn check_aggregate_prefix() {
+        let mut trie: Trie<_, _, Sum> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77), ("ant", 3)].iter().cloned().collect();
+
+        assert_eq!(Some(Sum(90)), trie.aggregate_prefix(""));
+        assert_eq!(Some(Sum(13)), trie.aggregate_prefix("ant"));
+        assert_eq!(Some(Sum(8)), trie.aggregate_prefix("anthe"));
+        assert_eq!(Some(Sum(0)), trie.aggregate_prefix("antz"));
+
+        trie.insert("anthem", 10);
+        trie.remove("anthemion");
+        assert_eq!(Some(Sum(15)), trie.aggregate_prefix("ant"));
+
+        // Summaries are stale until written back
+        trie.values_mut().for_each(|v| *v *= 2);
+        assert_eq!(None, trie.aggregate_prefix("ant"));
+
+        trie.refresh_aggregates();
+        assert_eq!(Some(Sum(30)), trie.aggregate_prefix("ant"));
+        assert_eq!(Some(Sum(184)), trie.aggregate_prefix(""));
+
+        // Positioning a mutable cursor hands out no mut values, so summaries stay fresh
+        let mut cursor = trie.cursor_mut();
+        cursor.seek("ant");
+        cursor.next();
+        drop(cursor);
+        assert_eq!(Some(Sum(30)), trie.aggregate_prefix("ant"));
+
+        // Only the touched value's path is refreshed
+        *trie.longest_prefix_match_mut("antics").unwrap().1 += 1;
+        let mut cursor = trie.cursor_mut();
+        cursor.seek("and");
+        *cursor.value_mut().unwrap() = 0;
+        drop(cursor);
+        trie.refresh_aggregates();
+        assert_eq!(Some(Sum(31)), trie.aggregate_prefix("ant"));
+        assert_eq!(Some(Sum(31)), trie.aggregate_prefix(""));
+
+        // Through a shared view, keys relative to the view's prefix
+        let view = trie.subtrie("anth");
+        assert_eq!(Some(Sum(20)), view.aggregate_prefix(""));
+        assert_eq!(Some(Sum(20)), view.aggregate_prefix("em"));
+        assert_eq!(Some(Sum(0)), view.aggregate_prefix("z"));
+
+        let mut trie: Trie<_, _, Max> = [("mouse", 1), ("mousepad", 9), ("moose", 3), ("map", 4)].iter().cloned().collect();
+        assert_eq!(Some(Max(Some(9))), trie.aggregate_prefix("mo"));
+
+        // Max isn't invertible, summaries are recomputed along the path
+        trie.remove("mousepad");
+        assert_eq!(Some(Max(Some(3))), trie.aggregate_prefix("mo"));
+
+        let right = trie.split_off("mou");
+        assert_eq!(Some(Max(Some(4))), trie.aggregate_prefix("m"));
+        assert_eq!(Some(Max(Some(1))), right.aggregate_prefix("m"));
+        assert_eq!(Some(Max(None)), Trie::<&str, i32, Max>::new().aggregate_prefix(""));
+    }
+
+    #[test]
+    fn check_get_or_insert_with() {
+        let mut trie: Trie<_, _, Sum> = [("anthem", 1), ("and", 2)].iter().cloned().collect();
+
+        *trie.get_or_insert_with_bytes(b"anthem", || 0) += 10;
+        assert_eq!(Some(&11), trie.search("anthem"));
+
+        // New keys splitting a label, ending at a fork and extending a key
+        assert_eq!(&mut 3, trie.get_or_insert_with_bytes(b"ant", || 3));
+        assert_eq!(&mut 4, trie.get_or_insert_with_bytes(b"an", || 4));
+        assert_eq!(&mut 5, trie.get_or_insert_with_bytes(b"anthems", || 5));
+        assert_eq!(&mut 6, trie.get_or_insert_with_bytes(b"", || 6));
+
+        assert_eq!(6, trie.len());
+        assert_eq!(6, trie.root().unwrap().key_count());
+        assert_eq!(Some(("ant".to_string(), &3)), trie.select(3));
+        assert_eq!(labels_helper(trie.labels()), BTreeSet::from(["an", "d", "t", "hem", "s"]));
+        trie.refresh_aggregates();
+        assert_eq!(Some(Sum(19)), trie.aggregate_prefix("ant"));
+        assert_eq!(Some(Sum(31)), trie.aggregate_prefix(""));
+    }
+
+    #[test]
+    fn check_typed_keys() {
+        use std::net::Ipv4Addr;
+
+        // Negative numbers sort first
+        let trie: Trie<i64, &str> = [(-5, "a"), (300, "b"), (0, "c"), (-300, "d"), (7, "e")].into_iter().collect();
+        assert_eq!(vec![-300, -5, 0, 7, 300], trie.keys().collect::<Vec<_>>());
+        assert_eq!(Some(&"e"), trie.search(7));
+        assert_eq!(Some((-5, &"a")), trie.pairs().nth(1));
+        assert_eq!(Some(300), trie.keys().next_back());
+
+        // Composite keys, prefixed by a complete first element
+        let mut trie: Trie<(u32, String), i32> = Trie::new();
+        trie.insert((2, "b".to_string()), 1);
+        trie.insert((1, "zz".to_string()), 2);
+        trie.insert((2, "a".to_string()), 3);
+        trie.insert((10, "a".to_string()), 4);
+
+        let keys = trie.keys().map(|(n, s)| format!("{}{}", n, s)).collect::<Vec<_>>();
+        assert_eq!(vec!["1zz", "2a", "2b", "10a"], keys);
+        assert_eq!(Some(((2, "a".to_string()), &3)), trie.successor((1, "zz".to_string())));
+        assert_eq!(Some(((10, "a".to_string()), 4)), trie.pop_last());
+
+        let trie: Trie<Ipv4Addr, i32> = [(Ipv4Addr::new(10, 0, 0, 2), 1), (Ipv4Addr::new(9, 1, 1, 1), 2)].into_iter().collect();
+        let pairs = trie.into_iter().collect::<Vec<_>>();
+        assert_eq!(vec![(Ipv4Addr::new(9, 1, 1, 1), 2), (Ipv4Addr::new(10, 0, 0, 2), 1)], pairs);
+
+        let trie: Trie<&str, i32> = [("anthem", 1), ("and", 2)].into_iter().collect();
+        assert_eq!(vec!["and".to_string(), "anthem".to_string()], trie.keys().collect::<Vec<String>>());
+    }
+
+    #[test]
+    fn check_double_ended_iter() {
+        let trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77), ("ant", 3)].iter().cloned().collect();
+
+        let keys = trie.keys().collect::<Vec<_>>();
+        assert_eq!(vec!["and", "ant", "anthem", "anthemion", "anti"], keys);
+
+        let keys = trie.keys().rev().collect::<Vec<_>>();
+        assert_eq!(vec!["anti", "anthemion", "anthem", "ant", "and"], keys);
+
+        assert_eq!(vec![&2, &7, &1, &3, &77], trie.values().rev().collect::<Vec<_>>());
+
+        // Front and back meet in the middle without overlap
+        let mut pairs = trie.pairs();
+        assert_eq!(Some(("and".to_string(), &77)), pairs.next());
+        assert_eq!(Some(("anti".to_string(), &2)), pairs.next_back());
+        assert_eq!(Some(("anthemion".to_string(), &7)), pairs.next_back());
+        assert_eq!(Some(("ant".to_string(), &3)), pairs.next());
+        assert_eq!(Some(("anthem".to_string(), &1)), pairs.next_back());
+        assert_eq!(None, pairs.next());
+        assert_eq!(None, pairs.next_back());
+
+        // The empty key is stored at the root, ahead of every other key
+        let mut trie: Trie<_, _> = [("a", 1), ("b", 2), ("", 3)].iter().cloned().collect();
+        assert_eq!(3, trie.len());
+
+        let mut keys = trie.keys();
+        assert_eq!(Some("".to_string()), keys.next());
+        assert_eq!(Some("b".to_string()), keys.next_back());
+        assert_eq!(Some("a".to_string()), keys.next());
+        assert_eq!(None, keys.next_back());
+
+        let mut values = trie.values_mut();
+        *values.next_back().unwrap() *= 10;
+        assert_eq!(Some(&mut 3), values.next());
+        assert_eq!(Some(&mut 1), values.next());
+        assert_eq!(None, values.next_back());
+
+        let mut iter = trie.iter_mut();
+        assert_eq!(Some((&b"b"[..], &mut 20)), iter.next_back());
+        assert_eq!(Some((&b""[..], &mut 3)), iter.next());
+        assert_eq!(Some((&b"a"[..], &mut 1)), iter.next());
+        assert_eq!(None, iter.next_back());
+
+        let trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().cloned().collect();
+        let mut pairs = trie.into_iter();
+        assert_eq!(Some(("anti".to_string(), 2)), pairs.next_back());
+        assert_eq!(Some(("and".to_string(), 77)), pairs.next());
+        assert_eq!(Some(("anthemion".to_string(), 7)), pairs.next_back());
+        assert_eq!(vec![("anthem".to_string(), 1)], pairs.collect::<Vec<_>>());
+    }
+
     #[test]
     fn check_leafpairs_iter() {
         let trie: Trie<_, _> = [("anthem", 1), ("anti", 2), ("anthemion", 7), ("and", 77)].iter().cloned().collect();